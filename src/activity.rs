@@ -0,0 +1,171 @@
+use dioxus::prelude::*;
+use std::{collections::VecDeque, fmt::Write, sync::Mutex, time::Duration};
+use tracing::{field::{Field, Visit}, Event, Level, Subscriber};
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::{filter::Targets, layer::{Context, SubscriberExt}, util::SubscriberInitExt, Layer};
+use crate::settings;
+
+// The panel keeps the most recent events in memory, everything goes to the log file
+const MAX_ENTRIES: usize = 500;
+const MAX_LOG_FILES: usize = 7;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+static ENTRIES: Mutex<VecDeque<ActivityEntry>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ActivityEntry {
+    pub time: String,
+    pub level: Level,
+    pub message: String,
+    pub fields: String,
+}
+
+// Splits an event into its message and the remaining `key=value` fields
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, "{}={} ", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, "{}={:?} ", field.name(), value);
+        }
+    }
+}
+
+struct ActivityLayer;
+
+impl<S: Subscriber> Layer<S> for ActivityLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let format = time::macros::format_description!("[hour]:[minute]:[second]");
+        let entry = ActivityEntry {
+            time: time::OffsetDateTime::now_utc().format(format).unwrap_or_default(),
+            level: *event.metadata().level(),
+            message: visitor.message,
+            fields: visitor.fields.trim_end().to_string(),
+        };
+
+        if let Ok(mut entries) = ENTRIES.lock() {
+            if entries.len() >= MAX_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+}
+
+pub fn log_dir() -> std::path::PathBuf {
+    settings::app_data_dir().join("logs")
+}
+
+// Writes the loader's events to a daily rotated file and feeds the activity panel
+pub fn init_logging() {
+    let targets = Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG)
+        .with_default(Level::WARN);
+
+    let file_layer = Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("cumheim")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir())
+        .ok()
+        .map(|appender| tracing_subscriber::fmt::layer().with_writer(appender).with_ansi(false));
+
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(ActivityLayer)
+        .with(targets)
+        .try_init();
+}
+
+pub fn recent_entries() -> Vec<ActivityEntry> {
+    ENTRIES.lock().map(|entries| entries.iter().cloned().collect()).unwrap_or_default()
+}
+
+fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::ERROR => "#800000",
+        Level::WARN => "#b8860b",
+        Level::INFO => "#1b2838",
+        _ => "#999",
+    }
+}
+
+#[component]
+pub fn ActivityPanel() -> Element {
+    let mut expanded = use_signal(|| false);
+    let mut entries = use_signal(recent_entries);
+
+    use_future(move || async move {
+        loop {
+            if expanded() {
+                entries.set(recent_entries());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    let toggle = move |_| {
+        if !expanded() {
+            entries.set(recent_entries());
+        }
+        expanded.set(!expanded());
+    };
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin: 20px 0;",
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center; cursor: pointer;",
+                onclick: toggle,
+                h2 {
+                    style: "margin: 0; font-size: 16px; color: #1b2838;",
+                    "Activity Log"
+                }
+                span { style: "color: #999; font-size: 12px;", if expanded() { "▲" } else { "▼" } }
+            }
+            if expanded() {
+                p {
+                    style: "margin: 10px 0; font-size: 12px; color: #999;",
+                    "Also written to: {log_dir().display()}"
+                }
+                div {
+                    style: "max-height: 300px; overflow-y: auto; font-family: monospace; font-size: 12px; background: #fafafa; border: 1px solid #eee; padding: 5px;",
+                    if entries.read().is_empty() {
+                        p { style: "margin: 0; color: #999;", "Nothing has happened yet." }
+                    }
+                    // Newest first so the latest step is visible without scrolling
+                    for (index, entry) in entries.read().iter().rev().enumerate() {
+                        div {
+                            key: "{index}",
+                            style: "padding: 2px 0; border-bottom: 1px solid #eee;",
+                            span { style: "color: #999;", "{entry.time} " }
+                            span { style: "color: {level_color(entry.level)}; font-weight: bold;", "{entry.level} " }
+                            "{entry.message}"
+                            if !entry.fields.is_empty() {
+                                span { style: "color: #999;", " {entry.fields}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
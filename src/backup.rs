@@ -0,0 +1,373 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, io::Write, path::{Path, PathBuf}};
+use crate::error::{LoaderError, PathContext};
+use crate::game;
+use crate::settings::{self, Settings};
+
+const VALHEIM_APP_ID: &str = "892970";
+const SAVE_FOLDERS: [&str; 4] = ["characters", "characters_local", "worlds", "worlds_local"];
+const SOURCES_ENTRY: &str = "sources.json";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SaveLocation {
+    pub label: String,
+    pub path: PathBuf,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created: i64,
+    pub size: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+struct BackupSources {
+    sources: HashMap<String, PathBuf>,
+}
+
+// Valheim's per-user data folders (saves, Player.log), whether or not they exist
+pub fn local_valheim_dirs() -> Vec<SaveLocation> {
+    let mut locations = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(profile) = std::env::var("USERPROFILE") {
+            locations.push(SaveLocation {
+                label: "local".to_string(),
+                path: PathBuf::from(profile).join("AppData\\LocalLow\\IronGate\\Valheim"),
+            });
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(home) = std::env::var("HOME") {
+            locations.push(SaveLocation {
+                label: "local".to_string(),
+                path: PathBuf::from(home).join("Library/Application Support/IronGate/Valheim"),
+            });
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(home) = std::env::var("HOME") {
+            locations.push(SaveLocation {
+                label: "local".to_string(),
+                path: PathBuf::from(&home).join(".config/unity3d/IronGate/Valheim"),
+            });
+            locations.push(SaveLocation {
+                label: "local_flatpak".to_string(),
+                path: PathBuf::from(&home).join(".var/app/com.valvesoftware.Steam/.config/unity3d/IronGate/Valheim"),
+            });
+        }
+    }
+
+    locations
+}
+
+// Every directory that may hold Valheim characters or worlds, local and Steam cloud
+pub fn find_save_locations() -> Vec<SaveLocation> {
+    let mut locations = local_valheim_dirs();
+
+    // Steam cloud saves live under userdata/<account id>/892970/remote
+    if let Some(steam_dir) = crate::find_steam_directory()
+        && let Ok(entries) = std::fs::read_dir(steam_dir.join("userdata")) {
+        for entry in entries.flatten() {
+            let remote = entry.path().join(VALHEIM_APP_ID).join("remote");
+            if remote.is_dir() {
+                locations.push(SaveLocation {
+                    label: format!("steam_{}", entry.file_name().to_string_lossy()),
+                    path: remote,
+                });
+            }
+        }
+    }
+
+    locations.retain(|location| SAVE_FOLDERS.iter().any(|folder| location.path.join(folder).is_dir()));
+    locations
+}
+
+pub fn backups_dir() -> PathBuf {
+    settings::app_data_dir().join("backups")
+}
+
+// Zips all save folders into a new backup and prunes the oldest ones beyond `retention`.
+// Returns None when there was nothing to back up.
+pub fn create_backup(retention: usize) -> Result<Option<PathBuf>, LoaderError> {
+    let backup_path = take_backup()?;
+    if backup_path.is_some() {
+        prune_backups(retention)?;
+    }
+
+    Ok(backup_path)
+}
+
+// Written under a temporary name and renamed once complete, so a failed backup
+// never shows up as a restorable one
+fn take_backup() -> Result<Option<PathBuf>, LoaderError> {
+    let locations = find_save_locations();
+    if locations.is_empty() {
+        return Ok(None);
+    }
+
+    let dir = backups_dir();
+    std::fs::create_dir_all(&dir).at(&dir)?;
+
+    // Millis and a counter keep backups taken within the same second apart
+    let created = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    let mut backup_path = dir.join(format!("saves-{}-{:03}.zip", created.as_secs(), created.subsec_millis()));
+    let mut counter = 1;
+    while backup_path.exists() {
+        backup_path = dir.join(format!("saves-{}-{:03}-{}.zip", created.as_secs(), created.subsec_millis(), counter));
+        counter += 1;
+    }
+    tracing::info!(path = %backup_path.display(), locations = locations.len(), "Backing up saves");
+
+    let partial_path = backup_path.with_extension("zip.partial");
+    if let Err(e) = write_backup(&locations, &partial_path) {
+        if let Err(remove_error) = std::fs::remove_file(&partial_path) {
+            tracing::debug!(path = %partial_path.display(), error = %remove_error, "Could not remove partial backup");
+        }
+        return Err(e);
+    }
+    std::fs::rename(&partial_path, &backup_path).at(&backup_path)?;
+
+    Ok(Some(backup_path))
+}
+
+fn write_backup(locations: &[SaveLocation], backup_path: &Path) -> Result<(), LoaderError> {
+    let archive_error = |source| LoaderError::Archive { path: backup_path.to_path_buf(), source };
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(backup_path).at(backup_path)?);
+    let options = zip::write::SimpleFileOptions::default();
+
+    let mut sources = HashMap::new();
+    for location in locations {
+        for folder in SAVE_FOLDERS {
+            let folder_path = location.path.join(folder);
+            for file in collect_files(&folder_path).at(&folder_path)? {
+                let Ok(relative) = file.strip_prefix(&location.path) else { continue; };
+                let entry_name = format!("{}/{}", location.label, relative.to_string_lossy().replace('\\', "/"));
+                writer.start_file(entry_name, options).map_err(archive_error)?;
+                std::io::copy(&mut std::fs::File::open(&file).at(&file)?, &mut writer).at(backup_path)?;
+            }
+        }
+        sources.insert(location.label.clone(), location.path.clone());
+    }
+
+    let sources = serde_json::to_string_pretty(&BackupSources { sources }).map_err(std::io::Error::other).at(backup_path)?;
+    writer.start_file(SOURCES_ENTRY, options).map_err(archive_error)?;
+    writer.write_all(sources.as_bytes()).at(backup_path)?;
+    writer.finish().map_err(archive_error)?;
+
+    Ok(())
+}
+
+// Newest first
+pub fn list_backups() -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = std::fs::read_dir(backups_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let created = path.file_name()?
+                .to_str()?
+                .strip_prefix("saves-")?
+                .strip_suffix(".zip")?
+                .split('-')
+                .next()?
+                .parse()
+                .ok()?;
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(BackupInfo { path, created, size })
+        })
+        .collect();
+
+    backups.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.path.cmp(&a.path)));
+    backups
+}
+
+pub fn prune_backups(retention: usize) -> Result<(), LoaderError> {
+    for backup in list_backups().into_iter().skip(retention.max(1)) {
+        tracing::info!(path = %backup.path.display(), "Deleting old save backup");
+        std::fs::remove_file(&backup.path).at(&backup.path)?;
+    }
+
+    Ok(())
+}
+
+// Writes every file of the backup back to the location it was taken from. The current
+// saves are backed up first; pruning waits until the restore is done so it can't delete
+// the backup being restored.
+pub fn restore_backup(backup_path: &Path, retention: usize) -> Result<usize, LoaderError> {
+    tracing::info!(path = %backup_path.display(), "Restoring save backup");
+    let archive_error = |source| LoaderError::Archive { path: backup_path.to_path_buf(), source };
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(backup_path).at(backup_path)?).map_err(archive_error)?;
+    let sources: BackupSources = serde_json::from_reader(archive.by_name(SOURCES_ENTRY).map_err(archive_error)?)
+        .map_err(|source| LoaderError::ManifestParse { what: backup_path.display().to_string(), source })?;
+    if let Some(current) = take_backup()? {
+        tracing::info!(path = %current.display(), "Backed up the current saves before restoring");
+    }
+
+    let mut restored = 0;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(archive_error)?;
+        if file.is_dir() || file.name() == SOURCES_ENTRY {
+            continue;
+        }
+
+        let Some(relative_path) = file.enclosed_name() else { continue; };
+        let mut components = relative_path.components();
+        let Some(label) = components.next() else { continue; };
+        let Some(target_root) = sources.sources.get(&*label.as_os_str().to_string_lossy()) else { continue; };
+
+        let outpath = target_root.join(components.as_path());
+        if let Some(p) = outpath.parent() {
+            std::fs::create_dir_all(p).at(p)?;
+        }
+        let mut outfile = std::fs::File::create(&outpath).at(&outpath)?;
+        std::io::copy(&mut file, &mut outfile).at(&outpath)?;
+        restored += 1;
+    }
+    prune_backups(retention)?;
+
+    Ok(restored)
+}
+
+pub fn collect_files(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(collect_files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+fn format_timestamp(unix_secs: i64) -> String {
+    let format = time::macros::format_description!("[year]-[month]-[day] [hour]:[minute] UTC");
+    time::OffsetDateTime::from_unix_timestamp(unix_secs)
+        .ok()
+        .and_then(|dt| dt.format(format).ok())
+        .unwrap_or_else(|| unix_secs.to_string())
+}
+
+#[component]
+pub fn BackupPanel(settings: Signal<Settings>, status: Signal<String>, disabled: bool) -> Element {
+    let mut backup_is_processing = use_signal(|| false);
+
+    let mut update_settings = move |update: Settings| {
+        if let Err(e) = update.save() {
+            status.set(format!("Error saving settings: {}", e));
+        }
+        settings.set(update);
+    };
+
+    let backup_now = move |_| {
+        spawn(async move {
+            backup_is_processing.set(true);
+            status.set("Backing up Valheim saves...".to_string());
+            match create_backup(settings().backup_retention) {
+                Ok(Some(path)) => { status.set(format!("Saves backed up to: {}", path.display())); }
+                Ok(None) => { status.set("No Valheim saves found to back up.".to_string()); }
+                Err(e) => { status.set(format!("Error backing up saves: {}", e.user_message())); }
+            }
+            backup_is_processing.set(false);
+        });
+    };
+
+    let busy = disabled || backup_is_processing();
+    // Re-read on every render so backups made by install/uninstall show up too
+    let backups = list_backups();
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "Save Backups"
+            }
+            label {
+                style: "display: flex; align-items: center; gap: 8px; font-size: 13px; color: #666; margin-bottom: 8px;",
+                input {
+                    r#type: "checkbox",
+                    checked: settings().backup_before_changes,
+                    onchange: move |e: FormEvent| {
+                        update_settings(Settings { backup_before_changes: e.checked(), ..settings() });
+                    },
+                }
+                "Back up before install/uninstall"
+            }
+            label {
+                style: "display: flex; align-items: center; gap: 8px; font-size: 13px; color: #666; margin-bottom: 10px;",
+                "Keep last"
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{settings().backup_retention}",
+                    style: "width: 50px;",
+                    onchange: move |e: FormEvent| {
+                        if let Ok(retention) = e.value().parse::<usize>() {
+                            update_settings(Settings { backup_retention: retention.max(1), ..settings() });
+                        }
+                    },
+                }
+                "backups"
+            }
+            button {
+                style: "background: #5c7e10; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-bottom: 10px;",
+                disabled: busy,
+                onclick: backup_now,
+                "Back Up Now"
+            }
+            if backups.is_empty() {
+                p {
+                    style: "margin: 0; color: #999; font-size: 13px;",
+                    "No backups yet."
+                }
+            }
+            for backup in backups.iter() {
+                div {
+                    key: "{backup.path.display()}",
+                    style: "display: flex; justify-content: space-between; align-items: center; font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    span { "{format_timestamp(backup.created)} ({backup.size / 1024} KB)" }
+                    button {
+                        style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        disabled: busy,
+                        onclick: {
+                            let backup_path = backup.path.clone();
+                            move |_| {
+                                let backup_path = backup_path.clone();
+                                spawn(async move {
+                                    backup_is_processing.set(true);
+                                    if let Err(e) = game::ensure_game_closed(settings().wait_for_game_exit, status).await {
+                                        status.set(format!("Restore cancelled: {}", e.user_message()));
+                                        backup_is_processing.set(false);
+                                        return;
+                                    }
+                                    match restore_backup(&backup_path, settings().backup_retention) {
+                                        Ok(count) => { status.set(format!("Restored {} save file(s) from backup.", count)); }
+                                        Err(e) => { status.set(format!("Error restoring backup: {}", e.user_message())); }
+                                    }
+                                    backup_is_processing.set(false);
+                                });
+                            }
+                        },
+                        "Restore"
+                    }
+                }
+            }
+        }
+    }
+}
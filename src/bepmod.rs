@@ -1,9 +1,159 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use crate::source::PackageSource;
 
-#[derive(Debug, Deserialize)]
+// How strongly the manifest wants a mod: required ones can't be deselected,
+// recommended ones start selected and optional ones start deselected
+#[derive(Clone, Copy, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Requirement {
+    Required,
+    #[default]
+    Recommended,
+    Optional,
+}
+
+// A package that can't be installed together with this one, either just its id
+// ("Namespace-Name") or `{ "id": ..., "reason": ... }` to tell players why
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ConflictEntry {
+    Id(String),
+    Detailed { id: String, reason: Option<String> },
+}
+
+impl ConflictEntry {
+    pub fn id(&self) -> &str {
+        match self {
+            ConflictEntry::Id(id) | ConflictEntry::Detailed { id, .. } => id.trim(),
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            ConflictEntry::Id(_) => None,
+            ConflictEntry::Detailed { reason, .. } => reason.as_deref(),
+        }
+    }
+}
+
+// One entry of mods.json. Thunderstore packages are referenced by namespace/name,
+// `github` ("owner/repo", with optional `tag` and `asset` pattern) or `url` pick another source
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct BepinexMod {
+    #[serde(default)]
     pub namespace: String,
     pub name: String,
     pub from: Option<String>,
     pub to: Option<String>,
-}
\ No newline at end of file
+    // Exact version the server runs, players are kept on it instead of the latest
+    pub version: Option<String>,
+    pub github: Option<String>,
+    pub tag: Option<String>,
+    pub asset: Option<String>,
+    pub url: Option<String>,
+    // Expected SHA-256 of the downloaded archive, checked before extracting
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub requirement: Requirement,
+    // Section the mod is listed under, `category` ("library", "skills", ...) stands in when unset
+    pub group: Option<String>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub conflicts_with: Vec<ConflictEntry>,
+    // Packages this one supersedes, selecting it deselects them
+    #[serde(default)]
+    pub replaces: Vec<String>,
+}
+
+impl BepinexMod {
+    pub fn source(&self) -> PackageSource {
+        if let Some(url) = self.url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+            return PackageSource::Url { url: url.to_string() };
+        }
+        if let Some((owner, repo)) = self.github.as_deref().and_then(|github| github.trim().split_once('/')) {
+            return PackageSource::GitHub {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                tag: self.tag.clone(),
+                asset: self.asset.clone(),
+            };
+        }
+
+        PackageSource::Thunderstore { namespace: self.namespace.trim().to_string(), name: self.name.trim().to_string() }
+    }
+
+    // Matches `Mod::id`; Thunderstore's `full_name`, otherwise namespace (or owner) and name
+    pub fn package_id(&self) -> String {
+        let namespace = match (self.namespace.trim(), self.source()) {
+            ("", PackageSource::GitHub { owner, .. }) => owner,
+            ("", PackageSource::Url { .. }) => "url".to_string(),
+            (namespace, _) => namespace.to_string(),
+        };
+        format!("{}-{}", namespace, self.name.trim())
+    }
+
+    pub fn pinned_version(&self) -> Option<&str> {
+        self.version.as_deref().map(str::trim).filter(|version| !version.is_empty())
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty()
+            && (!self.namespace.trim().is_empty() || !matches!(self.source(), PackageSource::Thunderstore { .. }))
+    }
+}
+
+// mods.json names the generic pack, Thunderstore modpacks depend on the Valheim one
+pub fn is_bepinex_pack(name: &str) -> bool {
+    name == "BepInExPack" || name == "BepInExPack_Valheim"
+}
+
+// Dedicated server the modpack is meant for
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ServerInfo {
+    // host:port
+    pub address: String,
+    pub password: Option<String>,
+    // Steam query port, Valheim uses the game port + 1 by default
+    pub query_port: Option<u16>,
+}
+
+// A server-managed BepInEx config file, merged into BepInEx/config
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ConfigPreset {
+    // Path below BepInEx/config, e.g. "com.example.mod.cfg"
+    pub file: String,
+    // The .cfg itself, or a zip (config package) containing a file of that name
+    pub url: String,
+    pub sha256: Option<String>,
+    // "Section.Key" or "Section.*" entries the server controls, nothing is changed when empty
+    #[serde(default)]
+    pub managed: Vec<String>,
+}
+
+// mods.json is either the plain list of mods, a single Thunderstore modpack reference
+// ("Namespace-Name" or "Namespace-Name-1.2.0"), or an object combining those with server settings
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Manifest {
+    Mods(Vec<BepinexMod>),
+    Modpack(String),
+    Full {
+        server: Option<ServerInfo>,
+        #[serde(default)]
+        mods: Vec<BepinexMod>,
+        modpack: Option<String>,
+        #[serde(default)]
+        configs: Vec<ConfigPreset>,
+    },
+}
+
+impl Manifest {
+    // (mods, server, modpack reference, config presets)
+    pub fn into_parts(self) -> (Vec<BepinexMod>, Option<ServerInfo>, Option<String>, Vec<ConfigPreset>) {
+        match self {
+            Manifest::Mods(mods) => (mods, None, None, Vec::new()),
+            Manifest::Modpack(modpack) => (Vec::new(), None, Some(modpack), Vec::new()),
+            Manifest::Full { server, mods, modpack, configs } => (mods, server, modpack, configs),
+        }
+    }
+}
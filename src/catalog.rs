@@ -0,0 +1,441 @@
+use dioxus::prelude::*;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::bepmod::{is_bepinex_pack, BepinexMod};
+use crate::error::{LoaderError, PathContext};
+use crate::settings::{self, Settings};
+use crate::{selection, source, Mod};
+
+const CATALOG_URL: &str = "https://thunderstore.io/c/valheim/api/v1/package/";
+const CACHE_FILE: &str = "catalog.json";
+// The index is tens of megabytes, it's only downloaded again once the cache is this old
+const CACHE_MAX_AGE: Duration = Duration::from_secs(6 * 60 * 60);
+const MAX_RESULTS: usize = 50;
+
+// The parsed catalog of this session, shared by the browser and the detail pane
+static LOADED: Mutex<Option<Arc<Vec<CatalogPackage>>>> = Mutex::new(None);
+
+// One package of the community index, only the fields the browser shows
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct CatalogPackage {
+    pub name: String,
+    pub full_name: String,
+    pub owner: String,
+    #[serde(default)]
+    pub date_updated: String,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    #[serde(default)]
+    pub has_nsfw_content: bool,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    pub versions: Vec<CatalogVersion>,
+    // Lowercased name, owner, description and categories, built once when the catalog loads
+    #[serde(skip)]
+    pub haystack: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct CatalogVersion {
+    pub version_number: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub icon: String,
+    #[serde(default)]
+    pub downloads: u64,
+    #[serde(default)]
+    pub date_created: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CatalogSort {
+    Downloads,
+    Updated,
+}
+
+impl CatalogPackage {
+    // Versions are listed newest first
+    pub fn latest(&self) -> Option<&CatalogVersion> {
+        self.versions.first()
+    }
+
+    pub fn total_downloads(&self) -> u64 {
+        self.versions.iter().map(|version| version.downloads).sum()
+    }
+
+    fn build_haystack(&mut self) {
+        self.haystack = format!(
+            "{} {} {} {}",
+            self.full_name,
+            self.owner,
+            self.latest().map(|version| version.description.as_str()).unwrap_or_default(),
+            self.categories.join(" ")
+        ).to_lowercase();
+    }
+
+    // Every (lowercased) word of the query has to appear in the name, owner, description or categories
+    pub fn matches(&self, words: &[String]) -> bool {
+        words.iter().all(|word| self.haystack.contains(word.as_str()))
+    }
+}
+
+fn cache_path() -> std::path::PathBuf {
+    settings::app_data_dir().join(CACHE_FILE)
+}
+
+fn parse_catalog(content: &str) -> Result<Vec<CatalogPackage>, LoaderError> {
+    let mut packages: Vec<CatalogPackage> = serde_json::from_str(content)
+        .map_err(|source| LoaderError::ManifestParse { what: "Thunderstore catalog".to_string(), source })?;
+    packages.iter_mut().for_each(CatalogPackage::build_haystack);
+    Ok(packages)
+}
+
+fn read_cache(max_age: Option<Duration>) -> Option<Vec<CatalogPackage>> {
+    let path = cache_path();
+    let age = std::fs::metadata(&path).ok()?.modified().ok()?.elapsed().unwrap_or_default();
+    if max_age.is_some_and(|max_age| age > max_age) {
+        return None;
+    }
+    parse_catalog(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+// Parsing the multi-megabyte catalog is kept off the UI thread
+async fn read_cache_blocking(max_age: Option<Duration>) -> Option<Vec<CatalogPackage>> {
+    tokio::task::spawn_blocking(move || read_cache(max_age)).await.ok().flatten()
+}
+
+fn remember(packages: Vec<CatalogPackage>) -> Arc<Vec<CatalogPackage>> {
+    let packages = Arc::new(packages);
+    if let Ok(mut loaded) = LOADED.lock() {
+        *loaded = Some(packages.clone());
+    }
+    packages
+}
+
+fn loaded() -> Option<Arc<Vec<CatalogPackage>>> {
+    LOADED.lock().ok()?.clone()
+}
+
+// What this session loaded, otherwise whatever was cached last, however old
+pub async fn cached_catalog() -> Option<Arc<Vec<CatalogPackage>>> {
+    if let Some(packages) = loaded() {
+        return Some(packages);
+    }
+    read_cache_blocking(None).await.map(remember)
+}
+
+// Served from memory or the local cache while it is fresh; a failed download falls back to a stale cache
+pub async fn load_catalog(force_refresh: bool) -> Result<Arc<Vec<CatalogPackage>>, LoaderError> {
+    if !force_refresh {
+        if let Some(packages) = loaded() {
+            return Ok(packages);
+        }
+        if let Some(packages) = read_cache_blocking(Some(CACHE_MAX_AGE)).await {
+            return Ok(remember(packages));
+        }
+    }
+
+    tracing::info!(url = CATALOG_URL, "Downloading Thunderstore catalog");
+    let content = match source::fetch_text(CATALOG_URL).await {
+        Ok(content) => content,
+        Err(e) => {
+            return match read_cache_blocking(None).await {
+                Some(packages) => {
+                    tracing::warn!(error = %e, "Catalog download failed, using the cached copy");
+                    Ok(remember(packages))
+                }
+                None => Err(e),
+            };
+        }
+    };
+
+    let packages = tokio::task::spawn_blocking(move || -> Result<Vec<CatalogPackage>, LoaderError> {
+        let packages = parse_catalog(&content)?;
+        let path = cache_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).at(dir)?;
+        }
+        std::fs::write(&path, &content).at(&path)?;
+        Ok(packages)
+    })
+    .await
+    .map_err(|e| LoaderError::io(&cache_path(), std::io::Error::other(e)))??;
+
+    Ok(remember(packages))
+}
+
+pub fn categories(packages: &[CatalogPackage]) -> Vec<String> {
+    let mut categories: Vec<String> = packages.iter().flat_map(|package| package.categories.iter().cloned()).collect();
+    categories.sort();
+    categories.dedup();
+    categories
+}
+
+pub fn search<'a>(
+    packages: &'a [CatalogPackage],
+    query: &str,
+    category: Option<&str>,
+    sort: CatalogSort,
+    show_deprecated: bool,
+    show_nsfw: bool,
+) -> Vec<&'a CatalogPackage> {
+    let words: Vec<String> = query.to_lowercase().split_whitespace().map(str::to_string).collect();
+    let mut results: Vec<&CatalogPackage> = packages.iter()
+        .filter(|package| show_deprecated || !package.is_deprecated)
+        .filter(|package| show_nsfw || !package.has_nsfw_content)
+        .filter(|package| category.is_none_or(|category| package.categories.iter().any(|c| c == category)))
+        .filter(|package| package.matches(&words))
+        .collect();
+
+    match sort {
+        CatalogSort::Downloads => results.sort_by_key(|package| std::cmp::Reverse(package.total_downloads())),
+        // ISO 8601 timestamps sort chronologically as strings
+        CatalogSort::Updated => results.sort_by(|a, b| b.date_updated.cmp(&a.date_updated)),
+    }
+    results
+}
+
+// Personal mods are plain Thunderstore references kept in the settings, installed next to
+// the server's pack at their latest version, or the one a profile import ("Namespace-Name-1.0.0") pinned
+pub fn personal_entry(reference: &str) -> Option<BepinexMod> {
+    source::dependency_entry(reference).map(|entry| BepinexMod { group: Some("Personal".to_string()), ..entry })
+}
+
+// The package a personal mod reference points at, matching `Mod::id`
+pub fn personal_id(reference: &str) -> Option<String> {
+    source::parse_reference(reference).map(|(namespace, name, _)| format!("{}-{}", namespace, name))
+}
+
+#[component]
+pub fn BrowsePanel(
+    mods: Signal<Vec<Mod>>,
+    mods_json_info: Signal<Vec<BepinexMod>>,
+    settings: Signal<Settings>,
+    status: Signal<String>,
+) -> Element {
+    let mut catalog = use_signal(|| Arc::new(Vec::<CatalogPackage>::new()));
+    let mut loading = use_signal(|| false);
+    let mut query = use_signal(String::new);
+    let mut category = use_signal(|| None::<String>);
+    let mut sort = use_signal(|| CatalogSort::Downloads);
+    let mut show_deprecated = use_signal(|| false);
+    let mut show_nsfw = use_signal(|| false);
+    // Packages being fetched, so a double click doesn't add them twice
+    let mut adding = use_signal(Vec::<String>::new);
+
+    let load = move |force_refresh: bool| {
+        spawn(async move {
+            loading.set(true);
+            status.set("Loading the Thunderstore catalog...".to_string());
+            match load_catalog(force_refresh).await {
+                Ok(packages) => {
+                    status.set(format!("Catalog loaded: {} package(s)", packages.len()));
+                    catalog.set(packages);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not load the Thunderstore catalog");
+                    status.set(format!("Error loading the catalog: {}", e.user_message()));
+                }
+            }
+            loading.set(false);
+        });
+    };
+
+    use_hook(move || load(false));
+
+    // Adds the package and, as personal mods too, the Thunderstore dependencies the list lacks
+    let mut add_package = move |full_name: String| {
+        if adding.read().contains(&full_name) || mods.read().iter().any(|mod_item| mod_item.id == full_name) {
+            return;
+        }
+        adding.write().push(full_name.clone());
+        spawn(async move {
+            let mut updated = settings();
+            let mut pending = vec![full_name.clone()];
+            let mut pulled_in = Vec::new();
+            while let Some(id) = pending.pop() {
+                if mods.read().iter().any(|mod_item| mod_item.id == id) {
+                    continue;
+                }
+                let Some(entry) = personal_entry(&id) else { continue; };
+                status.set(format!("Fetching {}...", id));
+                match source::resolve(&entry).await {
+                    Ok(mod_item) => {
+                        tracing::info!(package = %id, version = %mod_item.version, "Added personal mod");
+                        // BepInEx comes with the manifest, as a personal mod it would be locked in for good
+                        pending.extend(mod_item.dependencies.iter()
+                            .map(|dependency| selection::dependency_id(dependency).to_string())
+                            .filter(|id| !is_bepinex_pack(id.split_once('-').map_or(id.as_str(), |(_, name)| name))));
+                        if id != full_name {
+                            pulled_in.push(mod_item.name.clone());
+                        }
+                        updated.personal_mods.push(id);
+                        mods_json_info.write().push(entry);
+                        mods.write().push(mod_item);
+                    }
+                    Err(e) => {
+                        tracing::warn!(package = %id, error = %e, "Could not fetch package");
+                        status.set(format!("Error fetching {}: {}", id, e.user_message()));
+                        if id == full_name {
+                            break;
+                        }
+                    }
+                }
+            }
+            adding.write().retain(|id| *id != full_name);
+
+            let Some(mod_item) = mods.read().iter().find(|mod_item| mod_item.id == full_name).cloned() else { return; };
+            selection::complete(&mut mods.write());
+            if let Err(e) = updated.save() {
+                status.set(format!("Error saving settings: {}", e));
+                return;
+            }
+            settings.set(updated);
+            status.set(if pulled_in.is_empty() {
+                format!("Added {} v{}, press Apply to install it", mod_item.name, mod_item.version)
+            } else {
+                format!("Added {} v{} with its dependencies {}, press Apply to install them", mod_item.name, mod_item.version, pulled_in.join(", "))
+            });
+        });
+    };
+
+    // Dropping it from the manifest leaves an installed copy to the orphan handling of the plan
+    let mut remove_package = move |full_name: String| {
+        let mut updated = settings();
+        updated.personal_mods.retain(|reference| personal_id(reference).as_deref() != Some(full_name.as_str()));
+        if let Err(e) = updated.save() {
+            status.set(format!("Error saving settings: {}", e));
+            return;
+        }
+        settings.set(updated);
+        mods_json_info.write().retain(|entry| entry.package_id() != full_name);
+        mods.write().retain(|mod_item| mod_item.id != full_name);
+        status.set(format!("Removed {} from your personal mods", full_name));
+    };
+
+    let all_categories = use_memo(move || categories(&catalog.read()));
+    // The total count and the shown page, rebuilt only when the catalog or the filters change
+    let results = use_memo(move || {
+        let catalog = catalog.read();
+        let found = search(&catalog, &query(), category().as_deref(), sort(), show_deprecated(), show_nsfw());
+        (found.len(), found.into_iter().take(MAX_RESULTS).cloned().collect::<Vec<CatalogPackage>>())
+    });
+    let (result_count, results) = results();
+    let listed: Vec<String> = mods.read().iter().map(|mod_item| mod_item.id.clone()).collect();
+    let personal: Vec<String> = settings().personal_mods.iter().filter_map(|reference| personal_id(reference)).collect();
+
+    rsx! {
+        div {
+            style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap; margin-bottom: 10px; font-size: 13px; color: #666;",
+            input {
+                r#type: "search",
+                placeholder: "Search Thunderstore...",
+                style: "flex: 1; min-width: 200px; padding: 6px;",
+                value: "{query}",
+                oninput: move |e: FormEvent| query.set(e.value()),
+            }
+            select {
+                onchange: move |e: FormEvent| category.set(Some(e.value()).filter(|value| !value.is_empty())),
+                option { value: "", "All categories" }
+                for name in all_categories.read().iter() {
+                    option { key: "{name}", value: "{name}", selected: category().as_deref() == Some(name.as_str()), "{name}" }
+                }
+            }
+            select {
+                onchange: move |e: FormEvent| sort.set(if e.value() == "updated" { CatalogSort::Updated } else { CatalogSort::Downloads }),
+                option { value: "downloads", selected: sort() == CatalogSort::Downloads, "Most downloaded" }
+                option { value: "updated", selected: sort() == CatalogSort::Updated, "Recently updated" }
+            }
+            label {
+                input { r#type: "checkbox", checked: show_deprecated(), onchange: move |e: FormEvent| show_deprecated.set(e.checked()) }
+                " Deprecated"
+            }
+            label {
+                input { r#type: "checkbox", checked: show_nsfw(), onchange: move |e: FormEvent| show_nsfw.set(e.checked()) }
+                " NSFW"
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                disabled: loading(),
+                onclick: move |_| load(true),
+                "Refresh"
+            }
+        }
+
+        if loading() {
+            p { style: "font-size: 13px; color: #999;", "Loading catalog..." }
+        } else {
+            p {
+                style: "font-size: 12px; color: #999; margin: 0 0 8px 0;",
+                "{result_count} package(s)"
+                if result_count > MAX_RESULTS { ", showing the first {MAX_RESULTS}" }
+            }
+        }
+
+        div {
+            style: "display: flex; flex-direction: column; gap: 10px;",
+            for package in results.iter() {
+                div {
+                    key: "{package.full_name}",
+                    style: "border: 1px solid #e0e0e0; border-radius: 4px; padding: 15px; background: #fafafa; display: flex; align-items: start; gap: 15px;",
+                    img {
+                        src: "{package.latest().map(|version| version.icon.as_str()).unwrap_or_default()}",
+                        style: "width: 64px; height: 64px; border-radius: 4px; object-fit: cover; flex-shrink: 0;",
+                        alt: "{package.name}"
+                    }
+                    div {
+                        style: "flex: 1;",
+                        h3 {
+                            style: "margin: 0 0 5px 0; font-size: 16px; color: #1b2838;",
+                            "{package.name} "
+                            span {
+                                style: "font-size: 13px; color: #999; font-weight: normal;",
+                                "v{package.latest().map(|version| version.version_number.as_str()).unwrap_or_default()} by {package.owner}"
+                            }
+                            if package.is_deprecated {
+                                span { style: "font-size: 11px; color: white; background: #800000; border-radius: 3px; padding: 1px 5px; margin-left: 6px;", "Deprecated" }
+                            }
+                            if package.has_nsfw_content {
+                                span { style: "font-size: 11px; color: white; background: #b8860b; border-radius: 3px; padding: 1px 5px; margin-left: 6px;", "NSFW" }
+                            }
+                        }
+                        p {
+                            style: "margin: 0; color: #666; font-size: 13px; line-height: 1.4;",
+                            "{package.latest().map(|version| version.description.as_str()).unwrap_or_default()}"
+                        }
+                        p {
+                            style: "margin: 5px 0 0 0; color: #999; font-size: 12px;",
+                            "{package.total_downloads()} downloads · updated {package.date_updated.get(..10).unwrap_or_default()} · {package.categories.join(\", \")}"
+                        }
+                    }
+                    if personal.contains(&package.full_name) {
+                        button {
+                            style: "background: #800000; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                            onclick: {
+                                let full_name = package.full_name.clone();
+                                move |_| remove_package(full_name.clone())
+                            },
+                            "Remove"
+                        }
+                    } else if listed.contains(&package.full_name) {
+                        span { style: "color: #5c7e10; font-size: 12px;", "In modpack" }
+                    } else {
+                        button {
+                            style: "background: #5c7e10; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                            disabled: adding.read().contains(&package.full_name),
+                            onclick: {
+                                let full_name = package.full_name.clone();
+                                move |_| add_package(full_name.clone())
+                            },
+                            "Add"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,431 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use crate::error::{LoaderError, PathContext};
+use crate::receipt::{self, InstallReceipt};
+use crate::settings::Settings;
+use crate::{dotnet, plugins, Mod};
+
+// What to do when two packages want to write the same file
+#[derive(Clone, Copy, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    // The higher assembly version wins for DLLs, other files stay with their first owner
+    #[default]
+    KeepNewest,
+    // Later packages move into their own folder under the target
+    Subfolders,
+    Fail,
+}
+
+// One archive entry and where it lands, relative to the Valheim directory
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlannedFile {
+    pub entry: String,
+    pub destination: PathBuf,
+}
+
+// A downloaded package with the files it is going to write
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlannedPackage {
+    pub id: String,
+    pub name: String,
+    pub archive: PathBuf,
+    pub from: Option<String>,
+    pub target: PathBuf,
+    pub files: Vec<PlannedFile>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Collision {
+    pub path: PathBuf,
+    pub owners: Vec<String>,
+    pub resolution: String,
+}
+
+fn archive_error(path: &Path) -> impl Fn(zip::result::ZipError) -> LoaderError + '_ {
+    move |source| LoaderError::Archive { path: path.to_path_buf(), source }
+}
+
+// Same mapping as extracting: entries under `from` (or all of them) go below `target`
+fn list_files(archive_path: &Path, from: Option<&str>, target: &Path) -> Result<Vec<PlannedFile>, LoaderError> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(archive_path).at(archive_path)?).map_err(archive_error(archive_path))?;
+    let prefix = from.map(|from| format!("{}/", from));
+    let mut files = Vec::new();
+
+    for i in 0..archive.len() {
+        let file = archive.by_index(i).map_err(archive_error(archive_path))?;
+        if file.is_dir() {
+            continue;
+        }
+        let Some(name) = file.enclosed_name() else { continue; };
+        let relative = match &prefix {
+            Some(prefix) => match name.to_string_lossy().replace('\\', "/").strip_prefix(prefix.as_str()) {
+                Some(relative) => PathBuf::from(relative),
+                None => continue,
+            },
+            None => name,
+        };
+        files.push(PlannedFile { entry: file.name().to_string(), destination: target.join(relative) });
+    }
+
+    Ok(files)
+}
+
+pub fn plan_package(valheim_path: &Path, mod_item: &Mod, archive: &Path) -> Result<PlannedPackage, LoaderError> {
+    let target = PathBuf::from(mod_item.to.clone().unwrap_or_default());
+    let files = list_files(archive, mod_item.from.as_deref(), &target)?;
+    tracing::debug!(package = %mod_item.id, files = files.len(), dir = %valheim_path.join(&target).display(), "Planned package files");
+
+    Ok(PlannedPackage {
+        id: mod_item.id.clone(),
+        name: mod_item.name.clone(),
+        archive: archive.to_path_buf(),
+        from: mod_item.from.clone(),
+        target,
+        files,
+    })
+}
+
+// Every Thunderstore archive carries these at its root. They still get extracted (the
+// manifest tells versions apart for installs without a receipt) but nobody owns them.
+const PACKAGE_METADATA: [&str; 4] = ["manifest.json", "icon.png", "README.md", "CHANGELOG.md"];
+
+fn is_package_metadata(file: &PlannedFile) -> bool {
+    PACKAGE_METADATA.iter().any(|name| file.entry.eq_ignore_ascii_case(name))
+}
+
+// Windows paths are case-insensitive and receipts may carry either separator
+fn normalized(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/").to_lowercase()
+}
+
+// Who writes what, by package id: installed packages that stay first, then the plan in order
+fn owners(packages: &[PlannedPackage], staying: &[&InstallReceipt]) -> Vec<(PathBuf, Vec<String>)> {
+    let mut owners: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let claims = staying.iter()
+        .flat_map(|r| r.files.iter().map(|file| (file.clone(), r.id.clone())))
+        .chain(packages.iter().flat_map(|p| p.files.iter()
+            .filter(|file| !is_package_metadata(file))
+            .map(|file| (file.destination.clone(), p.id.clone()))));
+
+    for (path, owner) in claims {
+        let key = normalized(&path);
+        match index.get(&key) {
+            Some(&at) => {
+                let ids = &mut owners[at].1;
+                if !ids.contains(&owner) {
+                    ids.push(owner);
+                }
+            }
+            None => {
+                index.insert(key, owners.len());
+                owners.push((path, vec![owner]));
+            }
+        }
+    }
+
+    // Clashes among installed packages alone predate this plan, it can't resolve them
+    owners.retain(|(_, ids)| ids.len() > 1 && ids.iter().any(|id| packages.iter().any(|p| p.id == *id)));
+    owners
+}
+
+fn owner_names(ids: &[String], packages: &[PlannedPackage], staying: &[&InstallReceipt]) -> Vec<String> {
+    ids.iter()
+        .map(|id| packages.iter().find(|p| p.id == *id).map(|p| p.name.clone())
+            .or_else(|| staying.iter().find(|r| r.id == *id).map(|r| r.name.clone()))
+            .unwrap_or_else(|| id.clone()))
+        .collect()
+}
+
+fn entry_bytes(archive_path: &Path, entry: &str) -> Option<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(archive_path).ok()?).ok()?;
+    let mut file = archive.by_name(entry).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn assembly_version(bytes: &[u8]) -> Option<String> {
+    dotnet::parse_assembly(bytes).map(|assembly| assembly.version)
+}
+
+// Keeps the file of the owner with the highest assembly version (DLLs only, otherwise
+// the first owner). Losing packages skip the file; the installed copy counts as an owner.
+fn keep_newest(valheim_path: &Path, packages: &mut [PlannedPackage], path: &Path, owner_ids: &[String], staying: &[&InstallReceipt]) -> String {
+    let is_assembly = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("dll"));
+    let key = normalized(path);
+    let mut candidates: Vec<(String, String, Option<String>)> = Vec::new();
+
+    if let Some(installed) = staying.iter().find(|r| owner_ids.contains(&r.id)) {
+        let version = is_assembly.then(|| std::fs::read(valheim_path.join(path)).ok()).flatten().and_then(|bytes| assembly_version(&bytes));
+        candidates.push((installed.id.clone(), installed.name.clone(), version));
+    }
+    for package in packages.iter().filter(|p| owner_ids.contains(&p.id)) {
+        let Some(file) = package.files.iter().find(|file| normalized(&file.destination) == key) else { continue; };
+        let version = is_assembly.then(|| entry_bytes(&package.archive, &file.entry)).flatten().and_then(|bytes| assembly_version(&bytes));
+        candidates.push((package.id.clone(), package.name.clone(), version));
+    }
+
+    // First owner wins ties and files without a version
+    let mut winner = 0;
+    for (index, (_, _, version)) in candidates.iter().enumerate().skip(1) {
+        let best = &candidates[winner].2;
+        if let (Some(version), Some(best)) = (version, best)
+            && plugins::compare_versions(version, best) == Ordering::Greater {
+            winner = index;
+        }
+    }
+
+    let (winner_id, winner_name, winner_version) = &candidates[winner];
+    for package in packages.iter_mut().filter(|p| owner_ids.contains(&p.id) && p.id != *winner_id) {
+        package.files.retain(|file| normalized(&file.destination) != key);
+    }
+
+    match winner_version {
+        Some(version) => format!("kept {} from {} (v{})", path.file_name().unwrap_or_default().to_string_lossy(), winner_name, version),
+        None => format!("kept the copy from {}", winner_name),
+    }
+}
+
+// Moves a package into its own folder below its target (plugins/Mod.dll → plugins/Namespace-Name/Mod.dll)
+fn move_to_subfolder(package: &mut PlannedPackage) -> Result<(), LoaderError> {
+    package.target = package.target.join(&package.id);
+    package.files = list_files(&package.archive, package.from.as_deref(), &package.target)?;
+    Ok(())
+}
+
+// Checks the planned files against each other and against installed packages that stay
+// (`replaced` are the ids the plan removes or updates), then applies the policy.
+// Returns what collided and how it was resolved; `Fail` and unresolvable collisions are errors.
+pub fn resolve(
+    valheim_path: &Path,
+    packages: &mut [PlannedPackage],
+    receipts: &[InstallReceipt],
+    replaced: &[String],
+    policy: CollisionPolicy,
+) -> Result<Vec<Collision>, LoaderError> {
+    // Disabled packages have their files parked outside the game folders
+    let staying: Vec<&InstallReceipt> = receipts.iter().filter(|r| !r.disabled && !replaced.contains(&r.id)).collect();
+    let found = owners(packages, &staying);
+    if found.is_empty() {
+        return Ok(Vec::new());
+    }
+    for (path, ids) in &found {
+        tracing::warn!(path = %path.display(), owners = ?ids, policy = ?policy, "File collision");
+    }
+
+    let mut collisions = Vec::new();
+    match policy {
+        CollisionPolicy::Fail => {
+            let (path, ids) = &found[0];
+            return Err(LoaderError::FileCollision { path: path.clone(), owners: owner_names(ids, packages, &staying) });
+        }
+        CollisionPolicy::KeepNewest => {
+            for (path, ids) in found {
+                let owners = owner_names(&ids, packages, &staying);
+                let resolution = keep_newest(valheim_path, packages, &path, &ids, &staying);
+                collisions.push(Collision { path, owners, resolution });
+            }
+        }
+        CollisionPolicy::Subfolders => {
+            // Everyone but the first owner of a colliding file moves out of the way
+            let mut moved: Vec<String> = Vec::new();
+            for (_, ids) in &found {
+                for id in ids.iter().skip(1) {
+                    if let Some(package) = packages.iter_mut().find(|p| p.id == *id && !moved.contains(&p.id)) {
+                        move_to_subfolder(package)?;
+                        moved.push(package.id.clone());
+                    }
+                }
+            }
+            // Files outside plugins (configs, patchers) still collide after moving
+            if let Some((path, ids)) = owners(packages, &staying).into_iter().next() {
+                return Err(LoaderError::FileCollision { path, owners: owner_names(&ids, packages, &staying) });
+            }
+            for (path, ids) in found {
+                let owners = owner_names(&ids, packages, &staying);
+                let resolution = format!("moved {} into their own folder", owners[1..].join(", "));
+                collisions.push(Collision { path, owners, resolution });
+            }
+        }
+    }
+
+    Ok(collisions)
+}
+
+// Downloaded archives are only needed until their package is extracted
+pub fn remove_archives(packages: &[PlannedPackage]) {
+    for package in packages {
+        if let Err(e) = std::fs::remove_file(&package.archive) {
+            tracing::debug!(path = %package.archive.display(), error = %e, "Could not remove downloaded archive");
+        }
+    }
+}
+
+// Extracts exactly the planned files of a package, returning their absolute paths
+pub fn extract_planned(valheim_path: &Path, package: &PlannedPackage) -> Result<Vec<PathBuf>, LoaderError> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&package.archive).at(&package.archive)?).map_err(archive_error(&package.archive))?;
+    let mut written_files = Vec::new();
+    tracing::info!(package = %package.id, dir = %valheim_path.join(&package.target).display(), files = package.files.len(), "Extracting package");
+
+    for planned in &package.files {
+        let mut file = archive.by_name(&planned.entry).map_err(archive_error(&package.archive))?;
+        let outpath = valheim_path.join(&planned.destination);
+        if let Some(parent) = outpath.parent() {
+            std::fs::create_dir_all(parent).at(parent)?;
+        }
+        let mut outfile = std::fs::File::create(&outpath).at(&outpath)?;
+        std::io::copy(&mut file, &mut outfile).at(&outpath)?;
+        written_files.push(outpath);
+    }
+
+    Ok(written_files)
+}
+
+// Files a package just wrote belong to it alone: other installed packages that listed them
+// forget them, so removing those packages later leaves the new copies in place.
+// Package metadata is shared by everyone installing into the same folder and stays listed.
+pub fn hand_over(valheim_path: &Path, receipts: &mut [InstallReceipt], package: &PlannedPackage) -> Result<(), LoaderError> {
+    let package_id = package.id.as_str();
+    let written: Vec<String> = package.files.iter()
+        .filter(|file| !is_package_metadata(file))
+        .map(|file| normalized(&file.destination))
+        .collect();
+
+    for other in receipts.iter_mut().filter(|r| r.id != package_id && !r.disabled) {
+        let before = other.files.len();
+        other.files.retain(|file| !written.contains(&normalized(file)));
+        if other.files.len() != before {
+            tracing::info!(package = %other.id, new_owner = package_id, files = before - other.files.len(), "Handing over overwritten files");
+            receipt::save_receipt(valheim_path, other)?;
+        }
+    }
+    Ok(())
+}
+
+#[component]
+pub fn CollisionPanel(collisions: Signal<Vec<Collision>>, settings: Signal<Settings>, status: Signal<String>) -> Element {
+    let set_policy = move |e: FormEvent| {
+        let mut update = settings();
+        update.collision_policy = match e.value().as_str() {
+            "subfolders" => CollisionPolicy::Subfolders,
+            "fail" => CollisionPolicy::Fail,
+            _ => CollisionPolicy::KeepNewest,
+        };
+        if let Err(e) = update.save() {
+            status.set(format!("Error saving settings: {}", e));
+        }
+        settings.set(update);
+    };
+    let policy = settings().collision_policy;
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "File Collisions"
+            }
+            label {
+                style: "display: flex; align-items: center; gap: 6px; font-size: 13px; color: #666;",
+                "When packages share a file:"
+                select {
+                    onchange: set_policy,
+                    option { value: "keep_newest", selected: policy == CollisionPolicy::KeepNewest, "Keep newest version" }
+                    option { value: "subfolders", selected: policy == CollisionPolicy::Subfolders, "Use per-package folders" }
+                    option { value: "fail", selected: policy == CollisionPolicy::Fail, "Stop the install" }
+                }
+            }
+            if collisions.read().is_empty() {
+                p { style: "margin: 8px 0 0 0; font-size: 12px; color: #999;", "No collisions in the last install." }
+            }
+            for (index, collision) in collisions.read().iter().enumerate() {
+                div {
+                    key: "{index}",
+                    style: "font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee; margin-top: 4px;",
+                    div { strong { "{collision.path.display()}" } }
+                    div { style: "font-size: 12px;", "{collision.owners.join(\", \")} → {collision.resolution}" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(id: &str, files: &[&str], disabled: bool) -> InstallReceipt {
+        InstallReceipt {
+            id: id.to_string(),
+            name: format!("{} name", id),
+            version: "1.0.0".to_string(),
+            files: files.iter().map(PathBuf::from).collect(),
+            disabled,
+        }
+    }
+
+    fn planned(id: &str, files: &[&str]) -> PlannedPackage {
+        PlannedPackage {
+            id: id.to_string(),
+            name: format!("{} name", id),
+            archive: PathBuf::from("unused.zip"),
+            from: None,
+            target: PathBuf::new(),
+            files: files.iter().map(|file| PlannedFile { entry: file.to_string(), destination: PathBuf::from(file) }).collect(),
+        }
+    }
+
+    #[test]
+    fn paths_collide_regardless_of_case_and_separators() {
+        let receipts = [installed("A-Old", &["BepInEx/plugins/Shared.cfg"], false)];
+        let mut packages = [planned("A-New", &["bepinex\\Plugins\\shared.CFG", "BepInEx/plugins/Own.cfg"])];
+
+        let found = resolve(Path::new("."), &mut packages, &receipts, &[], CollisionPolicy::KeepNewest).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].owners, ["A-Old name", "A-New name"]);
+        assert_eq!(packages[0].files.len(), 1);
+    }
+
+    #[test]
+    fn disabled_packages_and_same_named_packages_are_told_apart() {
+        let receipts = [installed("A-Parked", &["BepInEx/plugins/Shared.cfg"], true)];
+        let mut packages = [planned("A-Mod", &["BepInEx/plugins/Shared.cfg"]), planned("B-Mod", &["BepInEx/plugins/Other.cfg"])];
+        packages[1].name = packages[0].name.clone();
+
+        let found = resolve(Path::new("."), &mut packages, &receipts, &[], CollisionPolicy::Fail).unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn package_metadata_never_collides() {
+        let receipts = [installed("A-Old", &["manifest.json", "icon.png"], false)];
+        let mut packages = [planned("A-Mod", &["manifest.json", "icon.png", "README.md", "CHANGELOG.md"]), planned("B-Mod", &["manifest.json", "README.md"])];
+
+        let found = resolve(Path::new("."), &mut packages, &receipts, &[], CollisionPolicy::Fail).unwrap();
+        assert!(found.is_empty());
+        assert_eq!(packages[0].files.len(), 4);
+    }
+
+    #[test]
+    fn overwritten_files_leave_the_old_receipt() {
+        let valheim_path = std::env::temp_dir().join(format!("collision-test-{}", std::process::id()));
+        let mut receipts = vec![installed("A-Old", &["BepInEx/plugins/Shared.dll", "BepInEx/plugins/Old.dll", "BepInEx/plugins/manifest.json"], false)];
+        let mut package = planned("A-New", &["shared.dll", "manifest.json"]);
+        package.target = PathBuf::from("BepInEx").join("plugins");
+        for file in &mut package.files {
+            file.destination = package.target.join(&file.entry);
+        }
+
+        hand_over(&valheim_path, &mut receipts, &package).unwrap();
+        assert_eq!(receipts[0].files, [PathBuf::from("BepInEx/plugins/Old.dll"), PathBuf::from("BepInEx/plugins/manifest.json")]);
+        assert_eq!(receipt::load_receipts(&valheim_path), receipts);
+
+        std::fs::remove_dir_all(&valheim_path).unwrap();
+    }
+}
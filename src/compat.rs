@@ -0,0 +1,105 @@
+use dioxus::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::receipt::{self, InstallReceipt};
+use crate::{update, Mod};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CompatStatus {
+    Match,
+    Mismatch { installed: String },
+    Missing,
+    // Right version, but parked by the player so BepInEx won't load it
+    Disabled,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompatRow {
+    pub id: String,
+    pub name: String,
+    pub required: String,
+    pub status: CompatStatus,
+}
+
+impl CompatRow {
+    pub fn is_compatible(&self) -> bool {
+        self.status == CompatStatus::Match
+    }
+}
+
+// Local state of every mod whose version the server pins
+pub fn build_matrix(valheim_path: &Path, mods: &[Mod], receipts: &[InstallReceipt]) -> Vec<CompatRow> {
+    mods.iter()
+        .filter(|mod_item| mod_item.required_by_server)
+        .map(|mod_item| {
+            let disabled = receipts.iter().any(|r| r.id == mod_item.id && r.disabled);
+            let status = match update::installed_version(valheim_path, mod_item, receipts) {
+                None => CompatStatus::Missing,
+                Some(installed) if installed != mod_item.version => CompatStatus::Mismatch { installed },
+                Some(_) if disabled => CompatStatus::Disabled,
+                Some(_) => CompatStatus::Match,
+            };
+
+            CompatRow {
+                id: mod_item.id.clone(),
+                name: mod_item.name.clone(),
+                required: mod_item.version.clone(),
+                status,
+            }
+        })
+        .collect()
+}
+
+// `on_sync` selects every required mod and applies the resulting plan
+#[component]
+pub fn CompatPanel(valheim_location: Signal<Option<PathBuf>>, mods: Signal<Vec<Mod>>, disabled: bool, on_sync: EventHandler<()>) -> Element {
+    let Some(valheim_path) = valheim_location() else { return rsx! {}; };
+    let rows = build_matrix(&valheim_path, &mods.read(), &receipt::load_receipts(&valheim_path));
+    if rows.is_empty() {
+        return rsx! {};
+    }
+    let incompatible = rows.iter().filter(|row| !row.is_compatible()).count();
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 10px;",
+                h2 {
+                    style: "margin: 0; font-size: 16px; color: #1b2838;",
+                    "Server Compatibility"
+                }
+                if incompatible == 0 {
+                    span { style: "color: #5c7e10; font-size: 12px; font-weight: bold;", "✓ In sync" }
+                } else {
+                    span { style: "color: #800000; font-size: 12px; font-weight: bold;", "{incompatible} mismatch(es)" }
+                }
+            }
+            div {
+                style: "display: flex; justify-content: space-between; font-size: 11px; color: #999; padding: 4px 0;",
+                span { "Mod" }
+                span { "Local / Server" }
+            }
+            for row in rows.iter() {
+                div {
+                    key: "{row.id}",
+                    style: "display: flex; justify-content: space-between; align-items: center; font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    span { "{row.name}" }
+                    match &row.status {
+                        CompatStatus::Match => rsx! { span { style: "color: #5c7e10;", "v{row.required} / v{row.required}" } },
+                        CompatStatus::Mismatch { installed } => rsx! { span { style: "color: #800000;", "v{installed} / v{row.required}" } },
+                        CompatStatus::Missing => rsx! { span { style: "color: #800000;", "missing / v{row.required}" } },
+                        CompatStatus::Disabled => rsx! { span { style: "color: #b8860b;", "disabled / v{row.required}" } },
+                    }
+                }
+            }
+            if incompatible > 0 {
+                button {
+                    style: "background: #1b2838; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-top: 10px;",
+                    disabled: disabled,
+                    onclick: move |_| on_sync.call(()),
+                    "Sync to Server"
+                }
+            }
+        }
+    }
+}
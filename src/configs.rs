@@ -0,0 +1,391 @@
+use dioxus::prelude::*;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use crate::bepmod::ConfigPreset;
+use crate::error::{LoaderError, PathContext};
+use crate::settings::Settings;
+use crate::{game, r2modman, source};
+
+// One managed key whose value changes, `old` is None when the player's file lacks it
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfigChange {
+    pub section: String,
+    pub key: String,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+// What applying a preset would do to one file. The fetched preset is kept so applying
+// merges it into the file as it is then, not as it was when previewed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PresetPreview {
+    pub file: String,
+    pub changes: Vec<ConfigChange>,
+    pub preset: String,
+    pub managed: Vec<String>,
+}
+
+fn section_header(line: &str) -> Option<&str> {
+    line.trim().strip_prefix('[').and_then(|rest| rest.strip_suffix(']'))
+}
+
+fn key_value(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+    line.split_once('=').map(|(key, value)| (key.trim(), value.trim()))
+}
+
+// (section, key, value) of every setting in a BepInEx .cfg
+pub fn parse_cfg(content: &str) -> Vec<(String, String, String)> {
+    let mut section = String::new();
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        if let Some(header) = section_header(line) {
+            section = header.to_string();
+        } else if let Some((key, value)) = key_value(line) {
+            entries.push((section.clone(), key.to_string(), value.to_string()));
+        }
+    }
+    entries
+}
+
+// Only keys the manifest names are managed, an empty list leaves the whole file alone
+pub fn is_managed(managed: &[String], section: &str, key: &str) -> bool {
+    managed.iter().any(|entry| {
+        let entry = entry.trim();
+        entry == format!("{}.{}", section, key) || entry == format!("{}.*", section)
+    })
+}
+
+// Adds a line to the end of the current section, ahead of the blank lines separating it from the next
+fn insert_after_content(lines: &mut Vec<String>, line: String) {
+    let at = lines.iter().rposition(|existing| !existing.trim().is_empty()).map_or(0, |index| index + 1);
+    lines.insert(at, line);
+}
+
+// Writes the preset's value of every managed key into the player's file, keeping their
+// comments, ordering and every other setting. Managed keys the file lacks are added to
+// their section (created at the end when missing). A missing file is replaced by the preset.
+pub fn merge(current: Option<&str>, preset: &str, managed: &[String]) -> (String, Vec<ConfigChange>) {
+    let wanted: Vec<(String, String, String)> = parse_cfg(preset).into_iter()
+        .filter(|(section, key, _)| is_managed(managed, section, key))
+        .collect();
+
+    let Some(current) = current else {
+        let changes = wanted.into_iter()
+            .map(|(section, key, new)| ConfigChange { section, key, old: None, new })
+            .collect();
+        return (preset.to_string(), changes);
+    };
+
+    let mut changes = Vec::new();
+    let mut seen: Vec<(String, String)> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut section = String::new();
+
+    // Keys of `section` the file never had, flushed before the next header
+    let missing_in = |section: &str, seen: &[(String, String)]| -> Vec<(String, String, String)> {
+        wanted.iter()
+            .filter(|(s, key, _)| s == section && !seen.iter().any(|(seen_section, seen_key)| seen_section == s && seen_key == key))
+            .cloned()
+            .collect()
+    };
+
+    for line in current.lines() {
+        if let Some(header) = section_header(line) {
+            for (s, key, new) in missing_in(&section, &seen) {
+                insert_after_content(&mut lines, format!("{} = {}", key, new));
+                seen.push((s.clone(), key.clone()));
+                changes.push(ConfigChange { section: s, key, old: None, new });
+            }
+            section = header.to_string();
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if let Some((key, value)) = key_value(line)
+            && let Some((_, _, new)) = wanted.iter().find(|(s, k, _)| *s == section && k == key)
+        {
+            seen.push((section.clone(), key.to_string()));
+            if value != new {
+                changes.push(ConfigChange { section: section.clone(), key: key.to_string(), old: Some(value.to_string()), new: new.clone() });
+                lines.push(format!("{} = {}", key, new));
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+
+    for (s, key, new) in missing_in(&section, &seen) {
+        insert_after_content(&mut lines, format!("{} = {}", key, new));
+        seen.push((s.clone(), key.clone()));
+        changes.push(ConfigChange { section: s, key, old: None, new });
+    }
+
+    // Sections the player's file doesn't have at all
+    let mut new_sections: Vec<String> = Vec::new();
+    for (s, _, _) in &wanted {
+        if !new_sections.contains(s) && !missing_in(s, &seen).is_empty() {
+            new_sections.push(s.clone());
+        }
+    }
+    for s in new_sections {
+        lines.push(String::new());
+        lines.push(format!("[{}]", s));
+        for (s, key, new) in missing_in(&s, &seen) {
+            lines.push(format!("{} = {}", key, new));
+            changes.push(ConfigChange { section: s, key, old: None, new });
+        }
+    }
+
+    let mut merged = lines.join("\n");
+    if current.ends_with('\n') {
+        merged.push('\n');
+    }
+    (merged, changes)
+}
+
+// Presets may only write below BepInEx/config
+fn config_path(valheim_path: &Path, file: &str) -> Option<PathBuf> {
+    let relative = Path::new(file.trim());
+    let safe = relative.components().all(|component| matches!(component, Component::Normal(_)));
+    (safe && !file.trim().is_empty()).then(|| valheim_path.join("BepInEx").join("config").join(relative))
+}
+
+// The preset's text, straight from a .cfg URL or out of a config package zip
+pub async fn fetch_preset(preset: &ConfigPreset) -> Result<String, LoaderError> {
+    let url = preset.url.trim();
+    tracing::info!(url, file = %preset.file, "Fetching config preset");
+    let response = source::http_client().get(url).send().await.map_err(|e| LoaderError::network(url, e))?;
+    if !response.status().is_success() {
+        return Err(LoaderError::HttpStatus { url: url.to_string(), status: response.status() });
+    }
+    let bytes = response.bytes().await.map_err(|e| LoaderError::network(url, e))?;
+
+    if let Some(expected) = &preset.sha256 {
+        let found = source::sha256_hex(&bytes);
+        if !found.eq_ignore_ascii_case(expected.trim()) {
+            return Err(LoaderError::HashMismatch { url: url.to_string(), expected: expected.clone(), found });
+        }
+    }
+
+    if !bytes.starts_with(b"PK") {
+        return Ok(String::from_utf8_lossy(&bytes).to_string());
+    }
+    let archive_error = |source| LoaderError::Archive { path: PathBuf::from(url), source };
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes.to_vec())).map_err(archive_error)?;
+    let file_name = Path::new(preset.file.trim()).file_name().unwrap_or_default().to_os_string();
+    let entry = archive.file_names()
+        .find(|name| !name.ends_with('/') && Path::new(name).file_name() == Some(file_name.as_os_str()))
+        .map(str::to_string)
+        .ok_or(LoaderError::MissingAsset { release: url.to_string(), pattern: preset.file.clone() })?;
+
+    let mut content = String::new();
+    archive.by_name(&entry).map_err(archive_error)?.read_to_string(&mut content).at(Path::new(&entry))?;
+    Ok(content)
+}
+
+pub async fn preview_presets(valheim_path: &Path, presets: &[ConfigPreset]) -> Result<Vec<PresetPreview>, LoaderError> {
+    let mut previews = Vec::new();
+    for preset in presets {
+        let Some(path) = config_path(valheim_path, &preset.file) else {
+            tracing::warn!(file = %preset.file, "Skipping config preset outside BepInEx/config");
+            continue;
+        };
+        let content = fetch_preset(preset).await?;
+        let content = content.trim_start_matches('\u{feff}').to_string();
+        let current = std::fs::read_to_string(&path).ok();
+        let (_, changes) = merge(current.as_deref(), &content, &preset.managed);
+        previews.push(PresetPreview { file: preset.file.trim().to_string(), changes, preset: content, managed: preset.managed.clone() });
+    }
+    Ok(previews)
+}
+
+// Merges again into the files as they are now, so edits made since the preview (by the
+// player or a mod writing its defaults) survive. Returns how many keys were updated.
+pub fn apply_previews(valheim_path: &Path, previews: &[PresetPreview]) -> Result<usize, LoaderError> {
+    let mut updated = 0;
+    for preview in previews {
+        let Some(path) = config_path(valheim_path, &preview.file) else { continue; };
+        let current = match std::fs::read_to_string(&path) {
+            Ok(current) => Some(current),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(LoaderError::io(&path, e)),
+        };
+        let (merged, changes) = merge(current.as_deref(), &preview.preset, &preview.managed);
+        if changes.is_empty() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).at(parent)?;
+        }
+        std::fs::write(&path, merged).at(&path)?;
+        tracing::info!(file = %preview.file, keys = changes.len(), "Applied config preset");
+        updated += changes.len();
+    }
+    Ok(updated)
+}
+
+#[component]
+pub fn ConfigPresetPanel(
+    valheim_location: Signal<Option<PathBuf>>,
+    presets: Signal<Vec<ConfigPreset>>,
+    settings: Signal<Settings>,
+    status: Signal<String>,
+    disabled: bool,
+) -> Element {
+    let mut previews = use_signal(|| None::<Vec<PresetPreview>>);
+    let mut working = use_signal(|| false);
+
+    let preview = move |_| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        let presets = presets();
+        spawn(async move {
+            working.set(true);
+            status.set("Checking server configs...".to_string());
+            match preview_presets(&valheim_path, &presets).await {
+                Ok(found) => {
+                    let count: usize = found.iter().map(|preview| preview.changes.len()).sum();
+                    status.set(if count == 0 { "Your configs already match the server.".to_string() } else { format!("{} config setting(s) differ from the server", count) });
+                    previews.set(Some(found));
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not fetch config presets");
+                    status.set(format!("Error fetching server configs: {}", e.user_message()));
+                }
+            }
+            working.set(false);
+        });
+    };
+
+    let apply = move |_| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        let Some(found) = previews() else { return; };
+        spawn(async move {
+            if let Err(e) = game::ensure_game_closed(settings().wait_for_game_exit, status).await {
+                status.set(format!("Config update cancelled: {}", e.user_message()));
+                return;
+            }
+            let backup_path = match r2modman::backup_configs(&valheim_path) {
+                Ok(path) => path,
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not back up configs, preset update cancelled");
+                    status.set(format!("Error backing up configs, nothing was changed: {}", e.user_message()));
+                    return;
+                }
+            };
+            match apply_previews(&valheim_path, &found) {
+                Ok(count) => {
+                    tracing::info!(settings = count, backup = %backup_path.display(), "Applied config presets");
+                    status.set(format!("Updated {} config setting(s) to the server's values, your previous configs are at: {}", count, backup_path.display()));
+                    previews.set(None);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not apply config presets");
+                    status.set(format!("Error writing configs: {}", e.user_message()));
+                }
+            }
+        });
+    };
+
+    if presets.read().is_empty() {
+        return rsx! {};
+    }
+    let pending: usize = previews.read().iter().flatten().map(|preview| preview.changes.len()).sum();
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "Server Configs"
+            }
+            p {
+                style: "margin: 0 0 8px 0; font-size: 12px; color: #999;",
+                "Only settings the server manages are changed, your other edits stay."
+            }
+            for preview in previews.read().iter().flatten().filter(|preview| !preview.changes.is_empty()) {
+                div {
+                    key: "{preview.file}",
+                    style: "font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    strong { "{preview.file}" }
+                    for change in preview.changes.iter() {
+                        div {
+                            key: "{change.section}.{change.key}",
+                            style: "font-size: 12px; font-family: monospace;",
+                            "[{change.section}] {change.key}: "
+                            span { style: "color: #800000;", "{change.old.as_deref().unwrap_or(\"(unset)\")}" }
+                            " → "
+                            span { style: "color: #5c7e10;", "{change.new}" }
+                        }
+                    }
+                }
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-right: 5px;",
+                disabled: disabled || working(),
+                onclick: preview,
+                "Preview Changes"
+            }
+            if pending > 0 {
+                button {
+                    style: "background: #1b2838; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                    disabled: disabled || working(),
+                    onclick: apply,
+                    "Apply {pending} Change(s)"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENT: &str = "## Settings file\n\n[General]\n# The speed\nSpeed = 1\nName = mine\n\n[Extra]\nOn = true\n";
+
+    fn managed(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn adds_a_missing_key_to_its_section() {
+        let (merged, changes) = merge(Some(CURRENT), "[General]\nSpeed = 1\nLimit = 5\n", &managed(&["General.*"]));
+
+        assert_eq!(changes, [ConfigChange { section: "General".to_string(), key: "Limit".to_string(), old: None, new: "5".to_string() }]);
+        assert_eq!(merged, "## Settings file\n\n[General]\n# The speed\nSpeed = 1\nName = mine\nLimit = 5\n\n[Extra]\nOn = true\n");
+    }
+
+    #[test]
+    fn appends_a_missing_section() {
+        let (merged, changes) = merge(Some(CURRENT), "[Server]\nPort = 2456\n", &managed(&["Server.Port"]));
+
+        assert_eq!(changes.len(), 1);
+        assert!(merged.starts_with(CURRENT.trim_end()));
+        assert!(merged.ends_with("\n[Server]\nPort = 2456\n"));
+    }
+
+    #[test]
+    fn unchanged_and_unmanaged_values_stay() {
+        let preset = "[General]\nSpeed = 1\nName = server\n";
+
+        let (merged, changes) = merge(Some(CURRENT), preset, &managed(&["General.Speed"]));
+        assert!(changes.is_empty());
+        assert_eq!(merged, CURRENT);
+
+        let (merged, changes) = merge(Some(CURRENT), preset, &[]);
+        assert!(changes.is_empty());
+        assert_eq!(merged, CURRENT);
+    }
+
+    #[test]
+    fn changes_a_managed_value_in_place() {
+        let (merged, changes) = merge(Some(CURRENT), "[General]\nSpeed = 2\n", &managed(&["General.Speed"]));
+
+        assert_eq!(changes[0].old.as_deref(), Some("1"));
+        assert_eq!(merged, CURRENT.replace("Speed = 1", "Speed = 2"));
+    }
+}
@@ -0,0 +1,73 @@
+use dioxus::prelude::*;
+use crate::Mod;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConflictPair {
+    pub first: String,
+    pub second: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ConflictPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} conflicts with {}: {}", self.first, self.second, self.reason)
+    }
+}
+
+// Why `mod_item` can't be installed next to `other`, declared on either side
+pub fn conflict_between(mod_item: &Mod, other: &Mod) -> Option<String> {
+    let declared = |a: &Mod, b: &Mod| -> Option<String> {
+        if let Some(entry) = a.conflicts_with.iter().find(|entry| entry.id() == b.id) {
+            return Some(entry.reason().map(str::to_string).unwrap_or_else(|| format!("{} is marked incompatible with {}", a.name, b.name)));
+        }
+        a.replaces.iter().any(|id| id.trim() == b.id).then(|| format!("{} replaces {}", a.name, b.name))
+    };
+
+    declared(mod_item, other).or_else(|| declared(other, mod_item))
+}
+
+// Every conflicting pair among the selected mods
+pub fn find_conflicts(mods: &[Mod]) -> Vec<ConflictPair> {
+    let selected: Vec<&Mod> = mods.iter().filter(|m| m.enabled).collect();
+    let mut pairs = Vec::new();
+
+    for (index, mod_item) in selected.iter().enumerate() {
+        for other in &selected[index + 1..] {
+            if let Some(reason) = conflict_between(mod_item, other) {
+                pairs.push(ConflictPair { first: mod_item.name.clone(), second: other.name.clone(), reason });
+            }
+        }
+    }
+
+    pairs
+}
+
+#[component]
+pub fn ConflictPanel(mods: Signal<Vec<Mod>>) -> Element {
+    let pairs = find_conflicts(&mods.read());
+    if pairs.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            style: "background: #fff3cd; border: 1px solid #ffc107; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #800000;",
+                "Conflicting Mods"
+            }
+            p {
+                style: "margin: 0 0 8px 0; font-size: 12px; color: #666;",
+                "Deselect one mod of each pair before installing."
+            }
+            for (index, pair) in pairs.iter().enumerate() {
+                div {
+                    key: "{index}",
+                    style: "font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    strong { "{pair.first} ✕ {pair.second}" }
+                    div { style: "font-size: 12px;", "{pair.reason}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use dioxus::prelude::*;
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag};
+use crate::catalog::{self, CatalogVersion};
+use crate::error::LoaderError;
+use crate::{source, Mod};
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ModDetails {
+    pub package_url: String,
+    pub website_url: String,
+    // Rendered HTML
+    pub readme: Option<String>,
+    pub changelog: Option<String>,
+    // Newest first, empty when the catalog couldn't be loaded
+    pub versions: Vec<CatalogVersion>,
+    pub dependencies: Vec<String>,
+    pub dependents: Vec<String>,
+}
+
+fn is_web_link(url: &str) -> bool {
+    let url = url.trim().to_lowercase();
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+// Markdown to HTML. Raw HTML in a README is dropped rather than injected into the app,
+// and links or images pointing anywhere but the web (javascript:, file:, ...) lose their target
+pub fn render_markdown(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH)
+        .filter(|event| !matches!(event, Event::Html(_) | Event::InlineHtml(_)))
+        .map(|event| match event {
+            Event::Start(Tag::Link { link_type, dest_url, title, id }) if !is_web_link(&dest_url) => {
+                Event::Start(Tag::Link { link_type, dest_url: CowStr::Borrowed(""), title, id })
+            }
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) if !is_web_link(&dest_url) => {
+                Event::Start(Tag::Image { link_type, dest_url: CowStr::Borrowed(""), title, id })
+            }
+            event => event,
+        });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html
+}
+
+// Thunderstore details of a mod; mods from other sources have none
+pub async fn fetch_details(mod_item: &Mod) -> Result<Option<ModDetails>, LoaderError> {
+    if !mod_item.thunderstore {
+        return Ok(None);
+    }
+    let Some((namespace, name, _)) = source::parse_reference(&mod_item.id) else {
+        return Err(LoaderError::InvalidReference { reference: mod_item.id.clone() });
+    };
+    let (package, release) = source::fetch_release(&namespace, &name, Some(&mod_item.version)).await?;
+
+    let readme = source::fetch_markdown(&namespace, &name, &release.version_number, "readme").await.ok().flatten();
+    let changelog = source::fetch_markdown(&namespace, &name, &release.version_number, "changelog").await.ok().flatten();
+
+    // Version history and reverse dependencies only exist in the community index
+    let catalog = match catalog::cached_catalog().await {
+        Some(packages) => packages,
+        None => catalog::load_catalog(false).await.unwrap_or_default(),
+    };
+    let versions = catalog.iter()
+        .find(|entry| entry.full_name == package.full_name)
+        .map(|entry| entry.versions.clone())
+        .unwrap_or_default();
+    let dependency_prefix = format!("{}-", package.full_name);
+    let mut dependents: Vec<String> = catalog.iter()
+        .filter(|entry| entry.latest().is_some_and(|latest| latest.dependencies.iter().any(|dependency| dependency.starts_with(&dependency_prefix))))
+        .map(|entry| entry.full_name.clone())
+        .collect();
+    dependents.sort();
+
+    Ok(Some(ModDetails {
+        package_url: package.package_url.clone(),
+        website_url: release.website_url.clone(),
+        readme: readme.as_deref().map(render_markdown),
+        changelog: changelog.as_deref().map(render_markdown),
+        versions,
+        dependencies: release.dependencies.clone(),
+        dependents,
+    }))
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DetailTab {
+    Readme,
+    Changelog,
+    Versions,
+    Dependencies,
+}
+
+#[component]
+pub fn DetailPane(mod_item: Mod, on_close: EventHandler<()>) -> Element {
+    let mut tab = use_signal(|| DetailTab::Readme);
+    let details = use_resource({
+        let mod_item = mod_item.clone();
+        move || {
+            let mod_item = mod_item.clone();
+            async move {
+                fetch_details(&mod_item).await.map_err(|e| {
+                    tracing::warn!(package = %mod_item.id, error = %e, "Could not fetch mod details");
+                    e.user_message()
+                })
+            }
+        }
+    });
+
+    let tab_style = |wanted: DetailTab| if tab() == wanted {
+        "background: #1b2838; color: white; padding: 6px 14px; border: none; border-radius: 3px 3px 0 0; cursor: pointer; font-size: 13px;"
+    } else {
+        "background: #e0e0e0; color: #1b2838; padding: 6px 14px; border: none; border-radius: 3px 3px 0 0; cursor: pointer; font-size: 13px;"
+    };
+
+    rsx! {
+        div {
+            style: "position: fixed; inset: 0; background: rgba(0,0,0,0.4); display: flex; justify-content: center; align-items: center; z-index: 10;",
+            onclick: move |_| on_close.call(()),
+            div {
+                style: "background: white; border-radius: 5px; padding: 20px; width: 80%; max-width: 900px; max-height: 85vh; overflow-y: auto;",
+                onclick: move |e| e.stop_propagation(),
+                div {
+                    style: "display: flex; align-items: center; gap: 15px; margin-bottom: 15px;",
+                    img {
+                        src: "{mod_item.icon_url}",
+                        style: "width: 64px; height: 64px; border-radius: 4px; object-fit: cover;",
+                        alt: "{mod_item.name}"
+                    }
+                    div {
+                        style: "flex: 1;",
+                        h2 {
+                            style: "margin: 0 0 5px 0; font-size: 18px; color: #1b2838;",
+                            "{mod_item.name} "
+                            span { style: "font-size: 13px; color: #999; font-weight: normal;", "v{mod_item.version}" }
+                        }
+                        p { style: "margin: 0; color: #666; font-size: 13px;", "{mod_item.description}" }
+                    }
+                    button {
+                        style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        onclick: move |_| on_close.call(()),
+                        "Close"
+                    }
+                }
+
+                match &*details.read() {
+                    None => rsx! { p { style: "font-size: 13px; color: #999;", "Loading details..." } },
+                    Some(Err(e)) => rsx! { p { style: "font-size: 13px; color: #800000;", "No Thunderstore details available: {e}" } },
+                    Some(Ok(None)) => rsx! { p { style: "font-size: 13px; color: #999;", "This mod isn't from Thunderstore, there is no README or version history to show." } },
+                    Some(Ok(Some(details))) => rsx! {
+                        div {
+                            style: "font-size: 12px; margin-bottom: 10px;",
+                            if !details.package_url.is_empty() {
+                                a { href: "{details.package_url}", target: "_blank", style: "margin-right: 15px;", "Thunderstore page" }
+                            }
+                            if !details.website_url.is_empty() {
+                                a { href: "{details.website_url}", target: "_blank", "Website" }
+                            }
+                        }
+                        div {
+                            style: "display: flex; gap: 4px; border-bottom: 2px solid #1b2838; margin-bottom: 15px;",
+                            button { style: tab_style(DetailTab::Readme), onclick: move |_| tab.set(DetailTab::Readme), "README" }
+                            button { style: tab_style(DetailTab::Changelog), onclick: move |_| tab.set(DetailTab::Changelog), "Changelog" }
+                            button { style: tab_style(DetailTab::Versions), onclick: move |_| tab.set(DetailTab::Versions), "Versions ({details.versions.len()})" }
+                            button { style: tab_style(DetailTab::Dependencies), onclick: move |_| tab.set(DetailTab::Dependencies), "Dependencies" }
+                        }
+                        match tab() {
+                            DetailTab::Readme => match &details.readme {
+                                Some(html) => rsx! { div { style: "font-size: 13px; color: #333; line-height: 1.5;", dangerous_inner_html: "{html}" } },
+                                None => rsx! { p { style: "font-size: 13px; color: #999;", "This package has no README." } },
+                            },
+                            DetailTab::Changelog => match &details.changelog {
+                                Some(html) => rsx! { div { style: "font-size: 13px; color: #333; line-height: 1.5;", dangerous_inner_html: "{html}" } },
+                                None => rsx! { p { style: "font-size: 13px; color: #999;", "This package has no changelog." } },
+                            },
+                            DetailTab::Versions => rsx! {
+                                if details.versions.is_empty() {
+                                    p { style: "font-size: 13px; color: #999;", "Version history is unavailable, the Thunderstore catalog could not be loaded." }
+                                }
+                                for version in details.versions.iter() {
+                                    div {
+                                        key: "{version.version_number}",
+                                        style: "display: flex; justify-content: space-between; font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                                        span {
+                                            if version.version_number == mod_item.version { strong { "v{version.version_number}" } } else { "v{version.version_number}" }
+                                        }
+                                        span { style: "color: #999; font-size: 12px;", "{version.date_created.get(..10).unwrap_or_default()} · {version.downloads} downloads" }
+                                    }
+                                }
+                            },
+                            DetailTab::Dependencies => rsx! {
+                                h3 { style: "margin: 0 0 5px 0; font-size: 14px; color: #1b2838;", "Depends on" }
+                                if details.dependencies.is_empty() {
+                                    p { style: "font-size: 13px; color: #999;", "Nothing" }
+                                }
+                                for dependency in details.dependencies.iter() {
+                                    div { key: "{dependency}", style: "font-size: 13px; color: #666; padding: 2px 0;", "{dependency}" }
+                                }
+                                h3 { style: "margin: 15px 0 5px 0; font-size: 14px; color: #1b2838;", "Used by ({details.dependents.len()})" }
+                                for dependent in details.dependents.iter() {
+                                    div { key: "{dependent}", style: "font-size: 13px; color: #666; padding: 2px 0;", "{dependent}" }
+                                }
+                            },
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_html_is_dropped() {
+        let html = render_markdown("<script>alert(1)</script>\n\nSome <b onclick=\"steal()\">bold</b> text");
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onclick"));
+        assert!(html.contains("Some "));
+        assert!(html.contains("bold"));
+    }
+
+    #[test]
+    fn links_and_images_off_the_web_lose_their_target() {
+        let html = render_markdown("[a](javascript:alert(1)) [b](file:///etc/passwd) ![c](data:image/png;base64,AAAA) <javascript:alert(2)>");
+
+        // The autolink keeps its text, only the targets go
+        for scheme in ["javascript:", "file:", "data:"] {
+            assert!(!html.contains(&format!("=\"{}", scheme)), "{} target kept in {}", scheme, html);
+        }
+        assert_eq!(html.matches("href=\"\"").count(), 3);
+        assert_eq!(html.matches("src=\"\"").count(), 1);
+    }
+
+    #[test]
+    fn web_links_are_kept() {
+        let html = render_markdown("[wiki](https://valheim.fandom.com) ![icon](http://example.com/icon.png)");
+
+        assert!(html.contains("href=\"https://valheim.fandom.com\""));
+        assert!(html.contains("src=\"http://example.com/icon.png\""));
+    }
+}
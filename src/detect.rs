@@ -0,0 +1,421 @@
+use dioxus::prelude::*;
+use serde::Deserialize;
+use std::{io::Write, path::{Path, PathBuf}};
+use crate::Mod;
+use crate::bepmod::is_bepinex_pack;
+use crate::error::{LoaderError, PathContext};
+use crate::receipt::{self, InstallReceipt};
+use crate::{backup, game, settings};
+
+// Files and folders a BepInExPack install puts next to valheim.exe
+const BEPINEX_ROOT_FILES: [&str; 7] = [
+    "changelog.txt",
+    "doorstop_config.ini",
+    ".doorstop_version",
+    "start_game_bepinex.sh",
+    "start_server_bepinex.sh",
+    "winhttp.dll",
+    "doorstop_libs",
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PackageFolder {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub path: PathBuf,
+}
+
+// What was found in the Valheim directory that the loader didn't put there
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ExistingInstall {
+    pub bepinex_version: Option<String>,
+    pub bepinex_managed: bool,
+    pub unmanaged_plugins: Vec<PathBuf>,
+    pub package_folders: Vec<PackageFolder>,
+    pub manager_profiles: Vec<PathBuf>,
+}
+
+impl ExistingInstall {
+    // BepInEx is present but wasn't installed (or adopted) by the loader
+    pub fn is_foreign(&self) -> bool {
+        self.bepinex_version.is_some() && !self.bepinex_managed
+    }
+
+    pub fn has_findings(&self) -> bool {
+        self.is_foreign() || !self.unmanaged_plugins.is_empty() || !self.package_folders.is_empty() || !self.manager_profiles.is_empty()
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageManifest {
+    name: String,
+    version_number: String,
+}
+
+// BepInEx is installed but has no receipt, without scanning the plugins
+pub fn has_foreign_bepinex(valheim_path: &Path) -> bool {
+    valheim_path.join("BepInEx").join("core").is_dir()
+        && !receipt::load_receipts(valheim_path).iter().any(|r| is_bepinex_pack(&r.name))
+}
+
+pub fn detect_existing(valheim_path: &Path) -> ExistingInstall {
+    let receipts = receipt::load_receipts(valheim_path);
+    let plugins_dir = valheim_path.join("BepInEx").join("plugins");
+
+    let bepinex_version = if valheim_path.join("BepInEx").join("core").is_dir() {
+        Some(detect_bepinex_version(valheim_path).unwrap_or_else(|| "unknown".to_string()))
+    } else {
+        None
+    };
+
+    let package_folders: Vec<PackageFolder> = std::fs::read_dir(&plugins_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| read_package_folder(&entry.path()))
+        .filter(|folder| !receipts.iter().any(|r| r.id == folder.id))
+        .collect();
+
+    let managed_files: Vec<PathBuf> = receipts.iter()
+        .flat_map(|r| r.files.iter().map(|file| valheim_path.join(file)))
+        .collect();
+    let unmanaged_plugins = backup::collect_files(&plugins_dir)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|file| file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("dll")))
+        .filter(|file| !managed_files.contains(file))
+        .filter(|file| !package_folders.iter().any(|folder| file.starts_with(&folder.path)))
+        .collect();
+
+    ExistingInstall {
+        bepinex_version,
+        bepinex_managed: receipts.iter().any(|r| is_bepinex_pack(&r.name)),
+        unmanaged_plugins,
+        package_folders,
+        manager_profiles: find_manager_profiles(),
+    }
+}
+
+// r2modman / Thunderstore Mod Manager install each package into plugins/<Namespace>-<Name>
+// together with its manifest.json
+fn read_package_folder(path: &Path) -> Option<PackageFolder> {
+    let content = std::fs::read_to_string(path.join("manifest.json")).ok()?;
+    let manifest: PackageManifest = serde_json::from_str(content.trim_start_matches('\u{feff}')).ok()?;
+
+    Some(PackageFolder {
+        id: path.file_name()?.to_string_lossy().to_string(),
+        name: manifest.name,
+        version: manifest.version_number,
+        path: path.to_path_buf(),
+    })
+}
+
+fn detect_bepinex_version(valheim_path: &Path) -> Option<String> {
+    if let Ok(bytes) = std::fs::read(valheim_path.join("BepInEx").join("core").join("BepInEx.dll"))
+        && let Some(version) = read_file_version(&bytes) {
+        return Some(version);
+    }
+
+    // The pack's changelog starts with the version it belongs to
+    let changelog = std::fs::read_to_string(valheim_path.join("changelog.txt")).ok()?;
+    changelog.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_ascii_digit()))
+        .find(|word| word.contains('.') && word.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())))
+        .map(str::to_string)
+}
+
+// Receipts hold the Thunderstore version ("5.4.2202") the plan compares against, while
+// the DLL only knows BepInEx's own ("5.4.22.0"). The pack's version is used when it
+// belongs to the same BepInEx release, otherwise the DLL's so the plan offers the update.
+fn pack_version(file_version: &str, bepinex_mod: Option<&Mod>) -> String {
+    let release: Vec<&str> = file_version.split('.').collect();
+    let Some(pack) = bepinex_mod else { return file_version.to_string(); };
+    let pack_parts: Vec<&str> = pack.version.split('.').collect();
+
+    let same_release = release.len() >= 3 && pack_parts.len() == 3
+        && release[..2] == pack_parts[..2]
+        && pack_parts[2].starts_with(release[2]);
+    if same_release { pack.version.clone() } else { file_version.to_string() }
+}
+
+// Pulls the FileVersion string out of a PE's VS_VERSION_INFO resource
+fn read_file_version(bytes: &[u8]) -> Option<String> {
+    let key: Vec<u8> = "FileVersion\0".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let start = bytes.windows(key.len()).position(|window| window == key.as_slice())? + key.len();
+
+    let value: Vec<u16> = bytes[start..]
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .skip_while(|c| *c == 0)
+        .take_while(|c| *c != 0)
+        .collect();
+    let version = String::from_utf16(&value).ok()?;
+
+    (!version.is_empty()).then_some(version)
+}
+
+fn find_manager_profiles() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(appdata) = std::env::var("APPDATA") {
+            roots.push(PathBuf::from(&appdata).join("r2modmanPlus-local"));
+            roots.push(PathBuf::from(&appdata).join("Thunderstore Mod Manager").join("DataFolder"));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(home) = std::env::var("HOME") {
+            roots.push(PathBuf::from(&home).join(".config/r2modmanPlus-local"));
+            roots.push(PathBuf::from(&home).join(".config/Thunderstore Mod Manager/DataFolder"));
+        }
+    }
+
+    roots.into_iter()
+        .flat_map(|root| std::fs::read_dir(root.join("Valheim").join("profiles")).into_iter().flatten().flatten())
+        .map(|entry| entry.path())
+        .filter(|path| path.join("BepInEx").is_dir())
+        .collect()
+}
+
+fn bepinex_root_files(valheim_path: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = Vec::new();
+    for name in BEPINEX_ROOT_FILES {
+        let path = valheim_path.join(name);
+        if path.is_dir() {
+            files.extend(backup::collect_files(&path)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+// Registers the existing BepInEx and package folders as if the loader had installed them
+pub fn adopt_existing(valheim_path: &Path, existing: &ExistingInstall, bepinex_mod: Option<&Mod>) -> Result<usize, LoaderError> {
+    let mut adopted = 0;
+
+    if existing.is_foreign() {
+        let core_dir = valheim_path.join("BepInEx").join("core");
+        let mut files = backup::collect_files(&core_dir).at(&core_dir)?;
+        files.extend(bepinex_root_files(valheim_path).at(valheim_path)?);
+
+        receipt::save_receipt(valheim_path, &InstallReceipt {
+            id: bepinex_mod.map(|m| m.id.clone()).unwrap_or_else(|| "BepInEx-BepInExPack".to_string()),
+            name: "BepInExPack".to_string(),
+            version: pack_version(existing.bepinex_version.as_deref().unwrap_or_default(), bepinex_mod),
+            files: files.iter().filter_map(|file| file.strip_prefix(valheim_path).ok()).map(Path::to_path_buf).collect(),
+            disabled: false,
+        })?;
+        adopted += 1;
+    }
+
+    for folder in &existing.package_folders {
+        let files = backup::collect_files(&folder.path).at(&folder.path)?;
+        receipt::save_receipt(valheim_path, &InstallReceipt {
+            id: folder.id.clone(),
+            name: folder.name.clone(),
+            version: folder.version.clone(),
+            files: files.iter().filter_map(|file| file.strip_prefix(valheim_path).ok()).map(Path::to_path_buf).collect(),
+            disabled: false,
+        })?;
+        adopted += 1;
+    }
+
+    Ok(adopted)
+}
+
+// Zips BepInEx and the doorstop files into the loader's data dir
+pub fn backup_existing(valheim_path: &Path) -> Result<PathBuf, LoaderError> {
+    let dir = settings::app_data_dir().join("installs");
+    std::fs::create_dir_all(&dir).at(&dir)?;
+
+    let created = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    let backup_path = dir.join(format!("bepinex-{}.zip", created));
+    let archive_error = |source| LoaderError::Archive { path: backup_path.clone(), source };
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&backup_path).at(&backup_path)?);
+    let options = zip::write::SimpleFileOptions::default();
+
+    let bepinex_dir = valheim_path.join("BepInEx");
+    let mut files = backup::collect_files(&bepinex_dir).at(&bepinex_dir)?;
+    files.extend(bepinex_root_files(valheim_path).at(valheim_path)?);
+
+    for file in files {
+        let Ok(relative) = file.strip_prefix(valheim_path) else { continue; };
+        writer.start_file(relative.to_string_lossy().replace('\\', "/"), options).map_err(archive_error)?;
+        writer.write_all(&std::fs::read(&file).at(&file)?).at(&backup_path)?;
+    }
+    writer.finish().map_err(archive_error)?;
+
+    Ok(backup_path)
+}
+
+#[component]
+pub fn ExistingInstallPanel(valheim_location: Signal<Option<PathBuf>>, mods: Signal<Vec<Mod>>, status: Signal<String>, disabled: bool) -> Element {
+    // Bumped after each action so the directory is scanned again
+    let mut refresh = use_signal(|| 0);
+    let mut confirm_replace = use_signal(|| false);
+
+    // Scanned once per action, and again whenever an install or uninstall finishes
+    let existing = use_memo(use_reactive!(|disabled| {
+        let _ = (refresh(), disabled);
+        valheim_location().map(|path| detect_existing(&path)).unwrap_or_default()
+    }));
+
+    let Some(valheim_path) = valheim_location() else { return rsx! {}; };
+    let existing = existing();
+    if !existing.has_findings() {
+        return rsx! {};
+    }
+
+    let adopt = {
+        let valheim_path = valheim_path.clone();
+        let existing = existing.clone();
+        move |_| {
+            let bepinex_mod = mods.read().iter().find(|m| is_bepinex_pack(&m.name)).cloned();
+            match adopt_existing(&valheim_path, &existing, bepinex_mod.as_ref()) {
+                Ok(count) => { status.set(format!("Adopted {} existing package(s).", count)); }
+                Err(e) => { status.set(format!("Error adopting existing install: {}", e.user_message())); }
+            }
+            refresh += 1;
+        }
+    };
+
+    let backup_install = {
+        let valheim_path = valheim_path.clone();
+        move |_| {
+            match backup_existing(&valheim_path) {
+                Ok(path) => { status.set(format!("Existing install backed up to: {}", path.display())); }
+                Err(e) => { status.set(format!("Error backing up existing install: {}", e.user_message())); }
+            }
+        }
+    };
+
+    let replace = {
+        let valheim_path = valheim_path.clone();
+        move |_| {
+            confirm_replace.set(false);
+            if let Err(e) = game::check_game_closed() {
+                status.set(e.user_message());
+                return;
+            }
+
+            // Never delete someone's configs and plugins without a copy to go back to
+            let backup_path = match backup_existing(&valheim_path) {
+                Ok(path) => path,
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not back up the existing install, not replacing it");
+                    status.set(format!("Error backing up existing install, nothing was removed: {}", e.user_message()));
+                    return;
+                }
+            };
+            match crate::uninstall(Some(valheim_path.clone())) {
+                Ok(_) => { status.set(format!("Removed the existing BepInEx install, a backup is at: {}", backup_path.display())); }
+                Err(e) => { status.set(format!("Error removing existing install: {}", e.user_message())); }
+            }
+            refresh += 1;
+        }
+    };
+
+    rsx! {
+        div {
+            style: "background: #fff3cd; border: 1px solid #ffc107; padding: 15px; border-radius: 5px; margin: 20px 0; font-size: 13px;",
+            p {
+                style: "margin: 0 0 10px 0; font-weight: bold;",
+                "Existing mod setup detected"
+            }
+            if existing.is_foreign() {
+                p {
+                    style: "margin: 0 0 5px 0;",
+                    "BepInEx {existing.bepinex_version.clone().unwrap_or_default()} is installed but wasn't set up by this loader."
+                }
+            }
+            if !existing.package_folders.is_empty() {
+                p {
+                    style: "margin: 0 0 5px 0;",
+                    "Mod manager package folders: "
+                    for folder in existing.package_folders.iter() {
+                        code { key: "{folder.id}", style: "margin-right: 5px;", "{folder.name} v{folder.version}" }
+                    }
+                }
+            }
+            if !existing.unmanaged_plugins.is_empty() {
+                p {
+                    style: "margin: 0 0 5px 0;",
+                    "{existing.unmanaged_plugins.len()} plugin DLL(s) not managed by the loader: "
+                    for plugin in existing.unmanaged_plugins.iter() {
+                        code {
+                            key: "{plugin.display()}",
+                            style: "margin-right: 5px;",
+                            "{plugin.file_name().unwrap_or_default().to_string_lossy()}"
+                        }
+                    }
+                }
+            }
+            if !existing.manager_profiles.is_empty() {
+                p {
+                    style: "margin: 0 0 5px 0;",
+                    "r2modman / Thunderstore Mod Manager profiles found: "
+                    for profile in existing.manager_profiles.iter() {
+                        code {
+                            key: "{profile.display()}",
+                            style: "margin-right: 5px;",
+                            "{profile.file_name().unwrap_or_default().to_string_lossy()}"
+                        }
+                    }
+                }
+            }
+            div {
+                style: "margin-top: 10px; display: flex; gap: 5px;",
+                if existing.is_foreign() || !existing.package_folders.is_empty() {
+                    button {
+                        style: "background: #5c7e10; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        disabled: disabled,
+                        onclick: adopt,
+                        "Adopt"
+                    }
+                }
+                if existing.bepinex_version.is_some() {
+                    button {
+                        style: "background: #1b2838; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        disabled: disabled,
+                        onclick: backup_install,
+                        "Back Up"
+                    }
+                }
+                if existing.is_foreign() && !confirm_replace() {
+                    button {
+                        style: "background: #800000; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        disabled: disabled,
+                        onclick: move |_| confirm_replace.set(true),
+                        "Replace"
+                    }
+                }
+            }
+            if existing.is_foreign() && confirm_replace() {
+                div {
+                    style: "margin-top: 10px;",
+                    p {
+                        style: "margin: 0 0 5px 0;",
+                        "Replacing deletes the whole BepInEx folder, including its configs and plugins. It is backed up first."
+                    }
+                    button {
+                        style: "background: #800000; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-right: 5px;",
+                        disabled: disabled,
+                        onclick: replace,
+                        "Back Up and Replace"
+                    }
+                    button {
+                        style: "background: #666; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        onclick: move |_| confirm_replace.set(false),
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use dioxus::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::bepmod::is_bepinex_pack;
+use crate::error::{LoaderError, PathContext};
+use crate::game;
+use crate::receipt::{self, InstallReceipt};
+
+pub const DOORSTOP_CONFIG: &str = "doorstop_config.ini";
+
+// Moves every file of an installed package into the loader's disabled area
+pub fn disable_mod(valheim_path: &Path, receipt: &InstallReceipt) -> Result<(), LoaderError> {
+    if receipt.disabled {
+        return Ok(());
+    }
+
+    let parked_dir = receipt::disabled_dir(valheim_path, &receipt.id);
+    tracing::info!(package = %receipt.id, "Disabling package");
+    for file in &receipt.files {
+        move_file(&valheim_path.join(file), &parked_dir.join(file)).at(&valheim_path.join(file))?;
+    }
+
+    receipt::save_receipt(valheim_path, &InstallReceipt { disabled: true, ..receipt.clone() })
+}
+
+// Moves a disabled package's files back to where they were installed
+pub fn enable_mod(valheim_path: &Path, receipt: &InstallReceipt) -> Result<(), LoaderError> {
+    if !receipt.disabled {
+        return Ok(());
+    }
+
+    let parked_dir = receipt::disabled_dir(valheim_path, &receipt.id);
+    tracing::info!(package = %receipt.id, "Enabling package");
+    for file in &receipt.files {
+        move_file(&parked_dir.join(file), &valheim_path.join(file)).at(&parked_dir.join(file))?;
+    }
+    if parked_dir.is_dir() {
+        std::fs::remove_dir_all(&parked_dir).at(&parked_dir)?;
+    }
+
+    receipt::save_receipt(valheim_path, &InstallReceipt { disabled: false, ..receipt.clone() })
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    // Files deleted by hand since install are simply skipped
+    if !from.is_file() {
+        return Ok(());
+    }
+    if let Some(p) = to.parent() {
+        std::fs::create_dir_all(p)?;
+    }
+
+    std::fs::rename(from, to)
+}
+
+// None when BepInEx (doorstop) is not installed at all
+pub fn bepinex_enabled(valheim_path: &Path) -> Option<bool> {
+    let content = std::fs::read_to_string(valheim_path.join(DOORSTOP_CONFIG)).ok()?;
+    let value = content.lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "enabled")
+        .map(|(_, value)| value.trim().to_lowercase())?;
+
+    Some(value == "true")
+}
+
+// Flips doorstop's `enabled` switch so Valheim starts without BepInEx, nothing is deleted
+pub fn set_bepinex_enabled(valheim_path: &Path, enabled: bool) -> Result<(), LoaderError> {
+    let config_path = valheim_path.join(DOORSTOP_CONFIG);
+    let content = std::fs::read_to_string(&config_path).at(&config_path)?;
+
+    let mut found = false;
+    let mut lines: Vec<String> = content.lines()
+        .map(|line| {
+            match line.split_once('=') {
+                Some((key, _)) if key.trim() == "enabled" => {
+                    found = true;
+                    format!("{}= {}", key, enabled)
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect();
+    if !found {
+        return Err(LoaderError::BepInExMissing { path: config_path });
+    }
+    lines.push(String::new());
+
+    std::fs::write(&config_path, lines.join("\n")).at(&config_path)?;
+
+    Ok(())
+}
+
+#[component]
+pub fn InstalledPanel(valheim_location: Signal<Option<PathBuf>>, status: Signal<String>, disabled: bool) -> Element {
+    // Bumped after each change so the receipts are re-read from disk
+    let mut refresh = use_signal(|| 0);
+
+    let Some(valheim_path) = valheim_location() else { return rsx! {}; };
+    let _ = refresh();
+    let receipts: Vec<InstallReceipt> = receipt::load_receipts(&valheim_path)
+        .into_iter()
+        .filter(|r| !is_bepinex_pack(&r.name))
+        .collect();
+    let bepinex_state = bepinex_enabled(&valheim_path);
+    let bepinex_is_enabled = bepinex_state.unwrap_or(true);
+
+    let toggle_vanilla = {
+        let valheim_path = valheim_path.clone();
+        move |_| {
+            if let Err(e) = game::check_game_closed() {
+                status.set(e.user_message());
+                return;
+            }
+            match set_bepinex_enabled(&valheim_path, !bepinex_is_enabled) {
+                Ok(_) if bepinex_is_enabled => { status.set("BepInEx disabled. Valheim will start vanilla.".to_string()); }
+                Ok(_) => { status.set("BepInEx enabled. Valheim will start modded.".to_string()); }
+                Err(e) => { status.set(format!("Error updating doorstop config: {}", e.user_message())); }
+            }
+            refresh += 1;
+        }
+    };
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "Installed Mods"
+            }
+            if bepinex_state.is_some() {
+                label {
+                    style: "display: flex; align-items: center; gap: 8px; font-size: 13px; color: #666; margin-bottom: 10px;",
+                    input {
+                        r#type: "checkbox",
+                        checked: !bepinex_is_enabled,
+                        disabled: disabled,
+                        onchange: toggle_vanilla,
+                    }
+                    "Play vanilla (disable BepInEx)"
+                }
+            }
+            if receipts.is_empty() {
+                p {
+                    style: "margin: 0; color: #999; font-size: 13px;",
+                    "No mods installed by the loader yet."
+                }
+            }
+            for installed in receipts {
+                div {
+                    key: "{installed.id}",
+                    style: "display: flex; justify-content: space-between; align-items: center; font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    span {
+                        style: if installed.disabled { "text-decoration: line-through; color: #999;" } else { "" },
+                        "{installed.name} v{installed.version}"
+                    }
+                    button {
+                        style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        disabled: disabled,
+                        onclick: {
+                            let valheim_path = valheim_path.clone();
+                            let installed = installed.clone();
+                            move |_| {
+                                if let Err(e) = game::check_game_closed() {
+                                    status.set(e.user_message());
+                                    return;
+                                }
+                                let result = if installed.disabled {
+                                    enable_mod(&valheim_path, &installed)
+                                } else {
+                                    disable_mod(&valheim_path, &installed)
+                                };
+                                match result {
+                                    Ok(_) if installed.disabled => { status.set(format!("Enabled {}", installed.name)); }
+                                    Ok(_) => { status.set(format!("Disabled {}", installed.name)); }
+                                    Err(e) => { status.set(format!("Error toggling {}: {}", installed.name, e)); }
+                                }
+                                refresh += 1;
+                            }
+                        },
+                        if installed.disabled { "Enable" } else { "Disable" }
+                    }
+                }
+            }
+        }
+    }
+}
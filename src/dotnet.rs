@@ -0,0 +1,654 @@
+// Minimal reader for the ECMA-335 (CLI) metadata of .NET assemblies. Only parses
+// what's needed to pull BepInEx plugin attributes and the assembly version out of
+// plugin DLLs without loading them.
+use std::path::Path;
+use crate::error::{LoaderError, PathContext};
+
+const TABLE_MODULE: usize = 0x00;
+const TABLE_TYPE_REF: usize = 0x01;
+const TABLE_TYPE_DEF: usize = 0x02;
+const TABLE_FIELD: usize = 0x04;
+const TABLE_METHOD_DEF: usize = 0x06;
+const TABLE_PARAM: usize = 0x08;
+const TABLE_INTERFACE_IMPL: usize = 0x09;
+const TABLE_MEMBER_REF: usize = 0x0A;
+const TABLE_CUSTOM_ATTRIBUTE: usize = 0x0C;
+const TABLE_DECL_SECURITY: usize = 0x0E;
+const TABLE_STAND_ALONE_SIG: usize = 0x11;
+const TABLE_EVENT: usize = 0x14;
+const TABLE_PROPERTY: usize = 0x17;
+const TABLE_MODULE_REF: usize = 0x1A;
+const TABLE_TYPE_SPEC: usize = 0x1B;
+const TABLE_ASSEMBLY: usize = 0x20;
+const TABLE_ASSEMBLY_REF: usize = 0x23;
+const TABLE_FILE: usize = 0x26;
+const TABLE_EXPORTED_TYPE: usize = 0x27;
+const TABLE_MANIFEST_RESOURCE: usize = 0x28;
+const TABLE_GENERIC_PARAM: usize = 0x2A;
+const TABLE_METHOD_SPEC: usize = 0x2B;
+const TABLE_GENERIC_PARAM_CONSTRAINT: usize = 0x2C;
+
+#[derive(Clone, Copy)]
+enum Coded {
+    TypeDefOrRef,
+    HasConstant,
+    HasCustomAttribute,
+    HasFieldMarshal,
+    HasDeclSecurity,
+    MemberRefParent,
+    HasSemantics,
+    MethodDefOrRef,
+    MemberForwarded,
+    CustomAttributeType,
+    ResolutionScope,
+}
+
+impl Coded {
+    fn tag_bits(self) -> u32 {
+        match self {
+            Coded::HasCustomAttribute => 5,
+            Coded::MemberRefParent | Coded::CustomAttributeType => 3,
+            Coded::TypeDefOrRef | Coded::HasConstant | Coded::HasDeclSecurity | Coded::ResolutionScope => 2,
+            _ => 1,
+        }
+    }
+
+    fn tables(self) -> &'static [usize] {
+        match self {
+            Coded::TypeDefOrRef => &[TABLE_TYPE_DEF, TABLE_TYPE_REF, TABLE_TYPE_SPEC],
+            Coded::HasConstant => &[TABLE_FIELD, TABLE_PARAM, TABLE_PROPERTY],
+            Coded::HasCustomAttribute => &[
+                TABLE_METHOD_DEF, TABLE_FIELD, TABLE_TYPE_REF, TABLE_TYPE_DEF, TABLE_PARAM,
+                TABLE_INTERFACE_IMPL, TABLE_MEMBER_REF, TABLE_MODULE, TABLE_DECL_SECURITY,
+                TABLE_PROPERTY, TABLE_EVENT, TABLE_STAND_ALONE_SIG, TABLE_MODULE_REF,
+                TABLE_TYPE_SPEC, TABLE_ASSEMBLY, TABLE_ASSEMBLY_REF, TABLE_FILE,
+                TABLE_EXPORTED_TYPE, TABLE_MANIFEST_RESOURCE, TABLE_GENERIC_PARAM,
+                TABLE_GENERIC_PARAM_CONSTRAINT, TABLE_METHOD_SPEC,
+            ],
+            Coded::HasFieldMarshal => &[TABLE_FIELD, TABLE_PARAM],
+            Coded::HasDeclSecurity => &[TABLE_TYPE_DEF, TABLE_METHOD_DEF, TABLE_ASSEMBLY],
+            Coded::MemberRefParent => &[TABLE_TYPE_DEF, TABLE_TYPE_REF, TABLE_MODULE_REF, TABLE_METHOD_DEF, TABLE_TYPE_SPEC],
+            Coded::HasSemantics => &[TABLE_EVENT, TABLE_PROPERTY],
+            Coded::MethodDefOrRef => &[TABLE_METHOD_DEF, TABLE_MEMBER_REF],
+            Coded::MemberForwarded => &[TABLE_FIELD, TABLE_METHOD_DEF],
+            Coded::CustomAttributeType => &[TABLE_METHOD_DEF, TABLE_MEMBER_REF],
+            Coded::ResolutionScope => &[TABLE_MODULE, TABLE_MODULE_REF, TABLE_ASSEMBLY_REF, TABLE_TYPE_REF],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Col {
+    U16,
+    U32,
+    Str,
+    Guid,
+    Blob,
+    Table(usize),
+    Coded(Coded),
+}
+
+// Column layout of every table up to and including Assembly (0x20); tables are
+// stored back to back so all of them are needed to find the ones we read
+const SCHEMA: [&[Col]; 0x21] = [
+    /* 0x00 Module */ &[Col::U16, Col::Str, Col::Guid, Col::Guid, Col::Guid],
+    /* 0x01 TypeRef */ &[Col::Coded(Coded::ResolutionScope), Col::Str, Col::Str],
+    /* 0x02 TypeDef */ &[Col::U32, Col::Str, Col::Str, Col::Coded(Coded::TypeDefOrRef), Col::Table(TABLE_FIELD), Col::Table(TABLE_METHOD_DEF)],
+    /* 0x03 FieldPtr */ &[Col::Table(TABLE_FIELD)],
+    /* 0x04 Field */ &[Col::U16, Col::Str, Col::Blob],
+    /* 0x05 MethodPtr */ &[Col::Table(TABLE_METHOD_DEF)],
+    /* 0x06 MethodDef */ &[Col::U32, Col::U16, Col::U16, Col::Str, Col::Blob, Col::Table(TABLE_PARAM)],
+    /* 0x07 ParamPtr */ &[Col::Table(TABLE_PARAM)],
+    /* 0x08 Param */ &[Col::U16, Col::U16, Col::Str],
+    /* 0x09 InterfaceImpl */ &[Col::Table(TABLE_TYPE_DEF), Col::Coded(Coded::TypeDefOrRef)],
+    /* 0x0A MemberRef */ &[Col::Coded(Coded::MemberRefParent), Col::Str, Col::Blob],
+    /* 0x0B Constant */ &[Col::U16, Col::Coded(Coded::HasConstant), Col::Blob],
+    /* 0x0C CustomAttribute */ &[Col::Coded(Coded::HasCustomAttribute), Col::Coded(Coded::CustomAttributeType), Col::Blob],
+    /* 0x0D FieldMarshal */ &[Col::Coded(Coded::HasFieldMarshal), Col::Blob],
+    /* 0x0E DeclSecurity */ &[Col::U16, Col::Coded(Coded::HasDeclSecurity), Col::Blob],
+    /* 0x0F ClassLayout */ &[Col::U16, Col::U32, Col::Table(TABLE_TYPE_DEF)],
+    /* 0x10 FieldLayout */ &[Col::U32, Col::Table(TABLE_FIELD)],
+    /* 0x11 StandAloneSig */ &[Col::Blob],
+    /* 0x12 EventMap */ &[Col::Table(TABLE_TYPE_DEF), Col::Table(TABLE_EVENT)],
+    /* 0x13 EventPtr */ &[Col::Table(TABLE_EVENT)],
+    /* 0x14 Event */ &[Col::U16, Col::Str, Col::Coded(Coded::TypeDefOrRef)],
+    /* 0x15 PropertyMap */ &[Col::Table(TABLE_TYPE_DEF), Col::Table(TABLE_PROPERTY)],
+    /* 0x16 PropertyPtr */ &[Col::Table(TABLE_PROPERTY)],
+    /* 0x17 Property */ &[Col::U16, Col::Str, Col::Blob],
+    /* 0x18 MethodSemantics */ &[Col::U16, Col::Table(TABLE_METHOD_DEF), Col::Coded(Coded::HasSemantics)],
+    /* 0x19 MethodImpl */ &[Col::Table(TABLE_TYPE_DEF), Col::Coded(Coded::MethodDefOrRef), Col::Coded(Coded::MethodDefOrRef)],
+    /* 0x1A ModuleRef */ &[Col::Str],
+    /* 0x1B TypeSpec */ &[Col::Blob],
+    /* 0x1C ImplMap */ &[Col::U16, Col::Coded(Coded::MemberForwarded), Col::Str, Col::Table(TABLE_MODULE_REF)],
+    /* 0x1D FieldRVA */ &[Col::U32, Col::Table(TABLE_FIELD)],
+    /* 0x1E EncLog */ &[Col::U32, Col::U32],
+    /* 0x1F EncMap */ &[Col::U32],
+    /* 0x20 Assembly */ &[Col::U32, Col::U16, Col::U16, Col::U16, Col::U16, Col::U32, Col::Blob, Col::Str, Col::Str],
+];
+
+// A custom attribute applied to a type, with its constructor arguments decoded
+#[derive(Clone, PartialEq, Debug)]
+pub struct TypeAttribute {
+    pub type_row: u32,
+    pub namespace: String,
+    pub name: String,
+    pub args: Vec<AttributeArg>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum AttributeArg {
+    String(Option<String>),
+    Int(i32),
+    Bool(bool),
+}
+
+impl AttributeArg {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeArg::String(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AssemblyMetadata {
+    pub name: String,
+    pub version: String,
+    pub type_attributes: Vec<TypeAttribute>,
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+// ECMA-335 II.23.2 compressed unsigned integer, returns (value, bytes used)
+fn compressed_u32(data: &[u8], offset: usize) -> Option<(u32, usize)> {
+    let b0 = *data.get(offset)? as u32;
+    if b0 & 0x80 == 0 {
+        Some((b0, 1))
+    } else if b0 & 0xC0 == 0x80 {
+        Some((((b0 & 0x3F) << 8) | *data.get(offset + 1)? as u32, 2))
+    } else if b0 & 0xE0 == 0xC0 {
+        let rest = data.get(offset + 1..offset + 4)?;
+        Some((((b0 & 0x1F) << 24) | (rest[0] as u32) << 16 | (rest[1] as u32) << 8 | rest[2] as u32, 4))
+    } else {
+        None
+    }
+}
+
+struct Metadata<'a> {
+    tables: &'a [u8],
+    strings: &'a [u8],
+    blob: &'a [u8],
+    heap_sizes: u8,
+    rows: [u32; 64],
+    table_offsets: [usize; SCHEMA.len()],
+}
+
+impl<'a> Metadata<'a> {
+    fn parse(image: &'a [u8]) -> Option<Metadata<'a>> {
+        let root = cli_metadata_root(image)?;
+        if u32_at(root, 0)? != 0x424A_5342 {
+            return None;
+        }
+
+        let version_length = u32_at(root, 12)? as usize;
+        let mut offset = 16 + version_length + 2;
+        let stream_count = u16_at(root, offset)?;
+        offset += 2;
+
+        let (mut tables, mut strings, mut blob) = (None, None, None);
+        for _ in 0..stream_count {
+            let stream_offset = u32_at(root, offset)? as usize;
+            let stream_size = u32_at(root, offset + 4)? as usize;
+            let name_start = offset + 8;
+            let name_length = root.get(name_start..)?.iter().position(|b| *b == 0)?;
+            let name = std::str::from_utf8(&root[name_start..name_start + name_length]).ok()?;
+            let stream = root.get(stream_offset..stream_offset + stream_size)?;
+            match name {
+                "#~" | "#-" => tables = Some(stream),
+                "#Strings" => strings = Some(stream),
+                "#Blob" => blob = Some(stream),
+                _ => {}
+            }
+            // Name is null terminated and padded to 4 bytes
+            offset = name_start + (name_length + 4) / 4 * 4;
+        }
+
+        let tables = tables?;
+        let heap_sizes = *tables.get(6)?;
+        let valid = u64_at(tables, 8)?;
+
+        let mut rows = [0u32; 64];
+        let mut offset = 24;
+        for (table, count) in rows.iter_mut().enumerate() {
+            if valid & (1 << table) != 0 {
+                *count = u32_at(tables, offset)?;
+                offset += 4;
+            }
+        }
+        if heap_sizes & 0x40 != 0 {
+            offset += 4;
+        }
+
+        let mut metadata = Metadata {
+            tables,
+            strings: strings.unwrap_or(&[]),
+            blob: blob.unwrap_or(&[]),
+            heap_sizes,
+            rows,
+            table_offsets: [0; SCHEMA.len()],
+        };
+        let mut table_offsets = [0; SCHEMA.len()];
+        for (table, table_offset) in table_offsets.iter_mut().enumerate() {
+            *table_offset = offset;
+            offset += metadata.row_size(table) * rows[table] as usize;
+        }
+        metadata.table_offsets = table_offsets;
+
+        Some(metadata)
+    }
+
+    fn col_size(&self, col: Col) -> usize {
+        match col {
+            Col::U16 => 2,
+            Col::U32 => 4,
+            Col::Str => if self.heap_sizes & 0x01 != 0 { 4 } else { 2 },
+            Col::Guid => if self.heap_sizes & 0x02 != 0 { 4 } else { 2 },
+            Col::Blob => if self.heap_sizes & 0x04 != 0 { 4 } else { 2 },
+            Col::Table(table) => if self.rows[table] > 0xFFFF { 4 } else { 2 },
+            Col::Coded(coded) => {
+                let max_rows = coded.tables().iter().map(|table| self.rows[*table]).max().unwrap_or(0);
+                if max_rows >= 1 << (16 - coded.tag_bits()) { 4 } else { 2 }
+            }
+        }
+    }
+
+    fn row_size(&self, table: usize) -> usize {
+        SCHEMA[table].iter().map(|col| self.col_size(*col)).sum()
+    }
+
+    // `row` is 1-based like metadata tokens
+    fn cell(&self, table: usize, row: u32, column: usize) -> Option<u32> {
+        if row == 0 || row > self.rows[table] {
+            return None;
+        }
+
+        let columns = SCHEMA[table];
+        let offset = self.table_offsets[table]
+            + self.row_size(table) * (row as usize - 1)
+            + columns[..column].iter().map(|col| self.col_size(*col)).sum::<usize>();
+        match self.col_size(columns[column]) {
+            2 => u16_at(self.tables, offset).map(u32::from),
+            _ => u32_at(self.tables, offset),
+        }
+    }
+
+    fn coded_cell(&self, table: usize, row: u32, column: usize, coded: Coded) -> Option<(usize, u32)> {
+        let value = self.cell(table, row, column)?;
+        let tag = value & ((1 << coded.tag_bits()) - 1);
+        Some((tag as usize, value >> coded.tag_bits()))
+    }
+
+    fn string(&self, index: u32) -> Option<String> {
+        let bytes = self.strings.get(index as usize..)?;
+        let end = bytes.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+    }
+
+    fn blob(&self, index: u32) -> Option<&'a [u8]> {
+        let (length, used) = compressed_u32(self.blob, index as usize)?;
+        let start = index as usize + used;
+        self.blob.get(start..start + length as usize)
+    }
+
+    fn type_name(&self, table: usize, row: u32) -> Option<(String, String)> {
+        // Name and namespace are the 2nd and 3rd column of both tables
+        if table != TABLE_TYPE_REF && table != TABLE_TYPE_DEF {
+            return None;
+        }
+        Some((self.string(self.cell(table, row, 2)?)?, self.string(self.cell(table, row, 1)?)?))
+    }
+}
+
+// Locates the CLI metadata root through the PE headers
+fn cli_metadata_root(image: &[u8]) -> Option<&[u8]> {
+    if image.get(0..2)? != b"MZ" {
+        return None;
+    }
+    let pe_offset = u32_at(image, 0x3C)? as usize;
+    if image.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let coff = pe_offset + 4;
+    let section_count = u16_at(image, coff + 2)? as usize;
+    let optional_size = u16_at(image, coff + 16)? as usize;
+    let optional = coff + 20;
+    let data_directories = match u16_at(image, optional)? {
+        0x10B => optional + 96,
+        0x20B => optional + 112,
+        _ => return None,
+    };
+
+    // Data directory 14 is the CLI header
+    let cli_rva = u32_at(image, data_directories + 14 * 8)?;
+    if cli_rva == 0 {
+        return None;
+    }
+
+    let sections = optional + optional_size;
+    let rva_to_offset = |rva: u32| -> Option<usize> {
+        (0..section_count).find_map(|i| {
+            let section = sections + i * 40;
+            let virtual_size = u32_at(image, section + 8)?;
+            let virtual_address = u32_at(image, section + 12)?;
+            let raw_size = u32_at(image, section + 16)?;
+            let raw_pointer = u32_at(image, section + 20)?;
+            let size = virtual_size.max(raw_size);
+            // Malformed headers mustn't overflow, those sections just don't match
+            let end = virtual_address.checked_add(size)?;
+            if rva < virtual_address || rva >= end {
+                return None;
+            }
+            (rva - virtual_address).checked_add(raw_pointer).map(|offset| offset as usize)
+        })
+    };
+
+    let cli_header = rva_to_offset(cli_rva)?;
+    let metadata_rva = u32_at(image, cli_header + 8)?;
+    let metadata_size = u32_at(image, cli_header + 12)? as usize;
+    let metadata = rva_to_offset(metadata_rva)?;
+
+    image.get(metadata..metadata.checked_add(metadata_size)?)
+}
+
+// Parameter types of a method signature, as far as attribute arguments need them
+#[derive(Clone, Copy)]
+enum ParamKind {
+    String,
+    Bool,
+    Int,
+}
+
+fn ctor_param_kinds(signature: &[u8]) -> Option<Vec<ParamKind>> {
+    let (count, used) = compressed_u32(signature, 1)?;
+    // Skip the calling convention, the parameter count and the (void) return type
+    let mut offset = 1 + used + 1;
+
+    let mut kinds = Vec::new();
+    for _ in 0..count {
+        let element_type = *signature.get(offset)?;
+        offset += 1;
+        kinds.push(match element_type {
+            0x0E => ParamKind::String,
+            0x02 => ParamKind::Bool,
+            0x08 | 0x09 => ParamKind::Int,
+            // Enums are value types, BepInEx only uses int-backed ones
+            0x11 => {
+                let (_, used) = compressed_u32(signature, offset)?;
+                offset += used;
+                ParamKind::Int
+            }
+            _ => return None,
+        });
+    }
+
+    Some(kinds)
+}
+
+fn attribute_args(value: &[u8], kinds: &[ParamKind]) -> Option<Vec<AttributeArg>> {
+    if u16_at(value, 0)? != 0x0001 {
+        return None;
+    }
+
+    let mut offset = 2;
+    let mut args = Vec::new();
+    for kind in kinds {
+        match kind {
+            ParamKind::String => {
+                if *value.get(offset)? == 0xFF {
+                    args.push(AttributeArg::String(None));
+                    offset += 1;
+                } else {
+                    let (length, used) = compressed_u32(value, offset)?;
+                    let start = offset + used;
+                    let bytes = value.get(start..start + length as usize)?;
+                    args.push(AttributeArg::String(Some(String::from_utf8_lossy(bytes).to_string())));
+                    offset = start + length as usize;
+                }
+            }
+            ParamKind::Bool => {
+                args.push(AttributeArg::Bool(*value.get(offset)? != 0));
+                offset += 1;
+            }
+            ParamKind::Int => {
+                args.push(AttributeArg::Int(u32_at(value, offset)? as i32));
+                offset += 4;
+            }
+        }
+    }
+
+    Some(args)
+}
+
+pub fn read_assembly(path: &Path) -> Result<AssemblyMetadata, LoaderError> {
+    let image = std::fs::read(path).at(path)?;
+    parse_assembly(&image).ok_or_else(|| LoaderError::InvalidAssembly { path: path.to_path_buf() })
+}
+
+pub fn parse_assembly(image: &[u8]) -> Option<AssemblyMetadata> {
+    let metadata = Metadata::parse(image)?;
+
+    let (name, version) = if metadata.rows[TABLE_ASSEMBLY] > 0 {
+        let version: Vec<String> = (1..=4)
+            .map(|column| metadata.cell(TABLE_ASSEMBLY, 1, column).unwrap_or(0).to_string())
+            .collect();
+        (metadata.string(metadata.cell(TABLE_ASSEMBLY, 1, 7)?)?, version.join("."))
+    } else {
+        (String::new(), String::new())
+    };
+
+    let mut type_attributes = Vec::new();
+    for row in 1..=metadata.rows[TABLE_CUSTOM_ATTRIBUTE] {
+        // Only attributes on types (tag 3 = TypeDef) built through a MemberRef (tag 3)
+        // constructor, which is how attributes from BepInEx.dll show up in plugins
+        let Some((3, type_row)) = metadata.coded_cell(TABLE_CUSTOM_ATTRIBUTE, row, 0, Coded::HasCustomAttribute) else { continue; };
+        let Some((3, member_row)) = metadata.coded_cell(TABLE_CUSTOM_ATTRIBUTE, row, 1, Coded::CustomAttributeType) else { continue; };
+
+        let Some((parent_tag, parent_row)) = metadata.coded_cell(TABLE_MEMBER_REF, member_row, 0, Coded::MemberRefParent) else { continue; };
+        let parent_table = Coded::MemberRefParent.tables()[parent_tag.min(4)];
+        let Some((namespace, attribute_name)) = metadata.type_name(parent_table, parent_row) else { continue; };
+
+        let Some(kinds) = metadata.cell(TABLE_MEMBER_REF, member_row, 2)
+            .and_then(|signature| metadata.blob(signature))
+            .and_then(ctor_param_kinds) else { continue; };
+        let Some(args) = metadata.cell(TABLE_CUSTOM_ATTRIBUTE, row, 2)
+            .and_then(|value| metadata.blob(value))
+            .and_then(|value| attribute_args(value, &kinds)) else { continue; };
+
+        type_attributes.push(TypeAttribute {
+            type_row,
+            namespace,
+            name: attribute_name,
+            args,
+        });
+    }
+
+    Some(AssemblyMetadata { name, version, type_attributes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_string(heap: &mut Vec<u8>, text: &str) -> u16 {
+        let index = heap.len() as u16;
+        heap.extend_from_slice(text.as_bytes());
+        heap.push(0);
+        index
+    }
+
+    fn push_blob(heap: &mut Vec<u8>, bytes: &[u8]) -> u16 {
+        let index = heap.len() as u16;
+        heap.push(bytes.len() as u8);
+        heap.extend_from_slice(bytes);
+        index
+    }
+
+    fn attribute_value(strings: &[&str], ints: &[i32]) -> Vec<u8> {
+        let mut value = vec![0x01, 0x00];
+        for text in strings {
+            value.push(text.len() as u8);
+            value.extend_from_slice(text.as_bytes());
+        }
+        for int in ints {
+            value.extend_from_slice(&int.to_le_bytes());
+        }
+        // No named arguments
+        value.extend_from_slice(&[0, 0]);
+        value
+    }
+
+    fn row(cells: &[u16]) -> Vec<u8> {
+        cells.iter().flat_map(|cell| cell.to_le_bytes()).collect()
+    }
+
+    fn padded(mut stream: Vec<u8>) -> Vec<u8> {
+        stream.resize(stream.len().div_ceil(4) * 4, 0);
+        stream
+    }
+
+    // A PE32 image holding the metadata of
+    //   [BepInPlugin("com.example.plugin", "Example Plugin", "1.2.3")]
+    //   [BepInDependency("com.example.core", DependencyFlags.HardDependency)]
+    //   class Example.ExamplePlugin
+    // in an assembly named Example, version 1.2.3.0
+    fn plugin_assembly() -> Vec<u8> {
+        let mut strings = vec![0];
+        let bepinex = push_string(&mut strings, "BepInEx");
+        let plugin_attribute = push_string(&mut strings, "BepInPlugin");
+        let dependency_attribute = push_string(&mut strings, "BepInDependency");
+        let example = push_string(&mut strings, "Example");
+        let plugin_class = push_string(&mut strings, "ExamplePlugin");
+        let ctor = push_string(&mut strings, ".ctor");
+
+        let mut blob = vec![0];
+        let plugin_ctor = push_blob(&mut blob, &[0x20, 3, 0x01, 0x0E, 0x0E, 0x0E]);
+        let dependency_ctor = push_blob(&mut blob, &[0x20, 2, 0x01, 0x0E, 0x11, 0x09]);
+        let plugin_value = push_blob(&mut blob, &attribute_value(&["com.example.plugin", "Example Plugin", "1.2.3"], &[]));
+        let dependency_value = push_blob(&mut blob, &attribute_value(&["com.example.core"], &[1]));
+
+        // TypeRef, TypeDef, MemberRef, CustomAttribute, Assembly
+        let present = [(TABLE_TYPE_REF, 2), (TABLE_TYPE_DEF, 1), (TABLE_MEMBER_REF, 2), (TABLE_CUSTOM_ATTRIBUTE, 2), (TABLE_ASSEMBLY, 1)];
+        let mut tables = vec![0, 0, 0, 0, 2, 0, 0, 1];
+        tables.extend_from_slice(&present.iter().fold(0u64, |valid, (table, _)| valid | 1 << table).to_le_bytes());
+        tables.extend_from_slice(&0u64.to_le_bytes());
+        for (_, rows) in present {
+            tables.extend_from_slice(&(rows as u32).to_le_bytes());
+        }
+        tables.extend(row(&[0, plugin_attribute, bepinex]));
+        tables.extend(row(&[0, dependency_attribute, bepinex]));
+        tables.extend(row(&[0, 0, plugin_class, example, 0, 1, 1]));
+        tables.extend(row(&[1 << 3 | 1, ctor, plugin_ctor]));
+        tables.extend(row(&[2 << 3 | 1, ctor, dependency_ctor]));
+        tables.extend(row(&[1 << 5 | 3, 1 << 3 | 3, plugin_value]));
+        tables.extend(row(&[1 << 5 | 3, 2 << 3 | 3, dependency_value]));
+        tables.extend(row(&[0x8004, 0, 1, 2, 3, 0, 0, 0, 0, example, 0]));
+
+        let streams = [("#~", padded(tables)), ("#Strings", padded(strings)), ("#Blob", padded(blob))];
+        let version = b"v4.0.30319\0\0";
+        let header_length = 16 + version.len() + 4
+            + streams.iter().map(|(name, _)| 8 + (name.len() + 4) / 4 * 4).sum::<usize>();
+
+        let mut root = vec![0x42, 0x53, 0x4A, 0x42, 1, 0, 1, 0, 0, 0, 0, 0];
+        root.extend_from_slice(&(version.len() as u32).to_le_bytes());
+        root.extend_from_slice(version);
+        root.extend_from_slice(&[0, 0, streams.len() as u8, 0]);
+        let mut offset = header_length;
+        for (name, stream) in &streams {
+            root.extend_from_slice(&(offset as u32).to_le_bytes());
+            root.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+            let mut padded_name = name.as_bytes().to_vec();
+            padded_name.resize((name.len() + 4) / 4 * 4, 0);
+            root.extend(padded_name);
+            offset += stream.len();
+        }
+        for (_, stream) in &streams {
+            root.extend_from_slice(stream);
+        }
+
+        // CLI header at RVA 0x2000 (file offset 0x200), metadata right behind it
+        let mut text = vec![0; 72];
+        text[0..4].copy_from_slice(&72u32.to_le_bytes());
+        text[8..12].copy_from_slice(&(0x2000u32 + 72).to_le_bytes());
+        text[12..16].copy_from_slice(&(root.len() as u32).to_le_bytes());
+        text.extend(root);
+
+        let mut image = vec![0; 0x200];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        image[0x86..0x88].copy_from_slice(&1u16.to_le_bytes());
+        image[0x94..0x96].copy_from_slice(&224u16.to_le_bytes());
+        image[0x98..0x9A].copy_from_slice(&0x10Bu16.to_le_bytes());
+        image[0x168..0x16C].copy_from_slice(&0x2000u32.to_le_bytes());
+        image[0x16C..0x170].copy_from_slice(&72u32.to_le_bytes());
+        let section = 0x98 + 224;
+        image[section..section + 5].copy_from_slice(b".text");
+        image[section + 8..section + 12].copy_from_slice(&(text.len() as u32).to_le_bytes());
+        image[section + 12..section + 16].copy_from_slice(&0x2000u32.to_le_bytes());
+        image[section + 16..section + 20].copy_from_slice(&(text.len() as u32).to_le_bytes());
+        image[section + 20..section + 24].copy_from_slice(&0x200u32.to_le_bytes());
+        image.extend(text);
+        image
+    }
+
+    fn text(value: &str) -> AttributeArg {
+        AttributeArg::String(Some(value.to_string()))
+    }
+
+    #[test]
+    fn reads_plugin_attributes_and_the_assembly_version() {
+        let assembly = parse_assembly(&plugin_assembly()).unwrap();
+        assert_eq!(assembly.name, "Example");
+        assert_eq!(assembly.version, "1.2.3.0");
+
+        let [plugin, dependency] = assembly.type_attributes.as_slice() else { panic!("{:?}", assembly.type_attributes) };
+        assert_eq!((plugin.namespace.as_str(), plugin.name.as_str(), plugin.type_row), ("BepInEx", "BepInPlugin", 1));
+        assert_eq!(plugin.args, [text("com.example.plugin"), text("Example Plugin"), text("1.2.3")]);
+        assert_eq!((dependency.name.as_str(), dependency.type_row), ("BepInDependency", 1));
+        assert_eq!(dependency.args, [text("com.example.core"), AttributeArg::Int(1)]);
+    }
+
+    #[test]
+    fn truncated_assemblies_are_rejected() {
+        let image = plugin_assembly();
+        for length in 0..image.len() {
+            assert!(parse_assembly(&image[..length]).is_none(), "accepted {} of {} bytes", length, image.len());
+        }
+    }
+
+    #[test]
+    fn garbage_never_panics() {
+        let image = plugin_assembly();
+        for index in 0..image.len() {
+            for byte in [0x00, 0x7F, 0x80, 0xFF] {
+                let mut corrupted = image.clone();
+                corrupted[index] = byte;
+                let _ = parse_assembly(&corrupted);
+            }
+        }
+        assert!(parse_assembly(b"MZ this is not a portable executable").is_none());
+        assert!(parse_assembly(&[0xFF; 4096]).is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use std::{path::{Path, PathBuf}, process::ExitCode};
+
+// Windows reports files held open by another process (the game, antivirus) as sharing violations
+const ERROR_SHARING_VIOLATION: i32 = 32;
+const ERROR_LOCK_VIOLATION: i32 = 33;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoaderError {
+    #[error("could not reach {url}: {source}")]
+    Network { url: String, source: reqwest::Error },
+
+    #[error("{url} returned HTTP {status}")]
+    HttpStatus { url: String, status: reqwest::StatusCode },
+
+    #[error("could not parse {what}: {source}")]
+    ManifestParse { what: String, source: serde_json::Error },
+
+    #[error("could not read archive {}: {source}", path.display())]
+    Archive { path: PathBuf, source: zip::result::ZipError },
+
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+
+    #[error("access to {} was denied", path.display())]
+    PermissionDenied { path: PathBuf, source: std::io::Error },
+
+    #[error("{reference} is not a valid package reference")]
+    InvalidReference { reference: String },
+
+    #[error("{name:?} can't be used as a mod folder name")]
+    InvalidPackageName { name: String },
+
+    #[error("no asset matching {pattern} in {release}")]
+    MissingAsset { release: String, pattern: String },
+
+    #[error("{package} resolved to version {found} but mods.json pins {expected}")]
+    VersionMismatch { package: String, expected: String, found: String },
+
+    #[error("{url} does not match its expected hash (expected {expected}, found {found})")]
+    HashMismatch { url: String, expected: String, found: String },
+
+    #[error("could not query server {address}: {source}")]
+    ServerQuery { address: String, source: std::io::Error },
+
+    #[error("{} would be written by more than one package ({})", path.display(), owners.join(", "))]
+    FileCollision { path: PathBuf, owners: Vec<String> },
+
+    #[error("{} is not a .NET assembly", path.display())]
+    InvalidAssembly { path: PathBuf },
+
+    #[error("BepInEx is not installed or {} has no enabled setting", path.display())]
+    BepInExMissing { path: PathBuf },
+
+    #[error("Valheim is running ({})", processes.join(", "))]
+    GameRunning { processes: Vec<String> },
+}
+
+impl LoaderError {
+    pub fn io(path: &Path, source: std::io::Error) -> LoaderError {
+        let locked = source.kind() == std::io::ErrorKind::PermissionDenied
+            || matches!(source.raw_os_error(), Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION));
+        if locked {
+            LoaderError::PermissionDenied { path: path.to_path_buf(), source }
+        } else {
+            LoaderError::Io { path: path.to_path_buf(), source }
+        }
+    }
+
+    pub fn network(url: &str, source: reqwest::Error) -> LoaderError {
+        LoaderError::Network { url: url.to_string(), source }
+    }
+
+    // What the user can do about it, shown after the error in the status line
+    pub fn guidance(&self) -> Option<&'static str> {
+        match self {
+            LoaderError::Network { .. } => Some("Check your internet connection and try again."),
+            LoaderError::HttpStatus { status, .. } if status.is_server_error() => Some("The server is having problems, try again in a few minutes."),
+            LoaderError::HttpStatus { .. } => Some("The package may have been removed or renamed, ask the server admin to update mods.json."),
+            LoaderError::ManifestParse { .. } => Some("The mod list is malformed, ask the server admin to check mods.json."),
+            LoaderError::Archive { .. } => Some("The download was corrupted, try again."),
+            LoaderError::Io { source, .. } if source.kind() == std::io::ErrorKind::StorageFull => Some("Free up some disk space and try again."),
+            LoaderError::Io { .. } => None,
+            LoaderError::PermissionDenied { path, .. } if path.ends_with("winhttp.dll") => {
+                Some("Your antivirus may have locked winhttp.dll, allow it or add an exception for the Valheim folder.")
+            }
+            LoaderError::PermissionDenied { .. } => Some("Close Valheim and anything else using the folder, then try again."),
+            LoaderError::InvalidReference { .. } => Some("Package references look like Namespace-Name or Namespace-Name-1.0.0, ask the server admin to check mods.json."),
+            LoaderError::InvalidPackageName { .. } => Some("Rename the mod's folder or fix the name in its manifest.json."),
+            LoaderError::MissingAsset { .. } => Some("The release doesn't contain the expected file, ask the server admin to update mods.json."),
+            LoaderError::VersionMismatch { .. } => Some("The release and the pinned version disagree, ask the server admin to set a matching tag in mods.json."),
+            LoaderError::HashMismatch { .. } => Some("The download was corrupted or changed since the manifest was written, try again or ask the server admin."),
+            LoaderError::ServerQuery { .. } => Some("The server may be offline or restarting."),
+            LoaderError::FileCollision { .. } => Some("Deselect one of the packages or pick another collision policy."),
+            LoaderError::InvalidAssembly { .. } => Some("The file is damaged or isn't a BepInEx plugin, reinstall the mod it came with."),
+            LoaderError::BepInExMissing { .. } => Some("Apply changes to install BepInEx before playing modded."),
+            LoaderError::GameRunning { .. } => Some("Close Valheim first, files can't be changed while the game is open."),
+        }
+    }
+
+    pub fn user_message(&self) -> String {
+        match self.guidance() {
+            Some(guidance) => format!("{} {}", self, guidance),
+            None => self.to_string(),
+        }
+    }
+}
+
+// Distinct exit codes per error class for command-line use
+impl From<&LoaderError> for ExitCode {
+    fn from(error: &LoaderError) -> ExitCode {
+        ExitCode::from(error.exit_code())
+    }
+}
+
+impl LoaderError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            LoaderError::Network { .. } => 10,
+            LoaderError::HttpStatus { .. } => 11,
+            LoaderError::ManifestParse { .. } => 12,
+            LoaderError::Archive { .. } => 13,
+            LoaderError::MissingAsset { .. } => 14,
+            LoaderError::HashMismatch { .. } => 15,
+            LoaderError::ServerQuery { .. } => 16,
+            LoaderError::InvalidReference { .. } => 17,
+            LoaderError::FileCollision { .. } => 18,
+            LoaderError::VersionMismatch { .. } => 19,
+            LoaderError::Io { .. } => 20,
+            LoaderError::PermissionDenied { .. } => 21,
+            LoaderError::InvalidPackageName { .. } => 22,
+            LoaderError::InvalidAssembly { .. } => 23,
+            LoaderError::BepInExMissing { .. } => 24,
+            LoaderError::GameRunning { .. } => 30,
+        }
+    }
+}
+
+// Attaches the path to IO errors: `std::fs::read(&path).at(&path)?`
+pub trait PathContext<T> {
+    fn at(self, path: &Path) -> Result<T, LoaderError>;
+}
+
+impl<T> PathContext<T> for Result<T, std::io::Error> {
+    fn at(self, path: &Path) -> Result<T, LoaderError> {
+        self.map_err(|source| LoaderError::io(path, source))
+    }
+}
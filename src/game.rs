@@ -0,0 +1,115 @@
+use dioxus::prelude::*;
+use std::time::Duration;
+use crate::error::LoaderError;
+
+// Executable names without extension; Linux truncates process names to 15 characters
+const GAME_PROCESSES: [&str; 2] = ["valheim", "valheim_server"];
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Long enough to finish a session, short enough that a forgotten game doesn't lock the loader
+const MAX_WAIT: Duration = Duration::from_secs(30 * 60);
+
+// Set while a change waits for the game to exit; clearing it cancels the wait
+static WAITING: GlobalSignal<bool> = Signal::global(|| false);
+
+fn is_game_process(name: &str) -> bool {
+    let stem = name.trim().split('.').next().unwrap_or_default().to_lowercase();
+    GAME_PROCESSES.contains(&stem.as_str())
+}
+
+#[cfg(target_os = "windows")]
+fn process_names() -> Vec<String> {
+    use std::process::Command;
+
+    // CSV rows look like "valheim.exe","1234","Console","1","1,234,567 K"
+    let Ok(output) = Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output() else { return Vec::new(); };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split("\",\"").next())
+        .map(|name| name.trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn process_names() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/proc") else { return Vec::new(); };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit()))
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn process_names() -> Vec<String> {
+    use std::process::Command;
+
+    let Ok(output) = Command::new("ps").args(["-A", "-c", "-o", "comm="]).output() else { return Vec::new(); };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+// Names of the running Valheim client/server processes, empty when the game is closed
+pub fn running_game_processes() -> Vec<String> {
+    let mut running: Vec<String> = process_names().into_iter().filter(|name| is_game_process(name)).collect();
+    running.sort();
+    running.dedup();
+    running
+}
+
+pub fn check_game_closed() -> Result<(), LoaderError> {
+    let processes = running_game_processes();
+    if processes.is_empty() {
+        Ok(())
+    } else {
+        tracing::warn!(processes = ?processes, "Valheim is running, refusing to modify files");
+        Err(LoaderError::GameRunning { processes })
+    }
+}
+
+// Call before writing to the game folder; with `wait` this blocks until the game exits instead
+// of failing, unless the wait is cancelled from the `WaitBanner` or runs past `MAX_WAIT`
+pub async fn ensure_game_closed(wait: bool, mut status: Signal<String>) -> Result<(), LoaderError> {
+    let start = std::time::Instant::now();
+    let mut waited = false;
+    let result = loop {
+        match check_game_closed() {
+            Err(LoaderError::GameRunning { processes }) if wait => {
+                let cancelled = waited && !*WAITING.peek();
+                if cancelled || start.elapsed() > MAX_WAIT {
+                    tracing::info!(processes = ?processes, waited = ?start.elapsed(), cancelled, "Stopped waiting for Valheim to close");
+                    break Err(LoaderError::GameRunning { processes });
+                }
+                if !waited {
+                    *WAITING.write() = true;
+                    waited = true;
+                }
+                status.set(format!("Waiting for {} to close...", processes.join(", ")));
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            result => break result,
+        }
+    };
+    if waited {
+        *WAITING.write() = false;
+    }
+    result
+}
+
+// Lets the user give up on a change that waits for the game to exit
+#[component]
+pub fn WaitBanner() -> Element {
+    if !WAITING() {
+        return rsx! {};
+    }
+
+    rsx! {
+        button {
+            style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-top: 8px;",
+            onclick: move |_| *WAITING.write() = false,
+            "Stop Waiting"
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use dioxus::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::bepmod::ServerInfo;
+use crate::error::{LoaderError, PathContext};
+use crate::{disable, game};
+
+const VALHEIM_APP_ID: &str = "892970";
+
+// Valheim's own command line options for joining a server on start
+pub fn launch_args(server: Option<&ServerInfo>, connect: bool) -> Vec<String> {
+    let Some(server) = server.filter(|_| connect) else { return Vec::new(); };
+
+    let mut args = vec!["+connect".to_string(), server.address.trim().to_string()];
+    if let Some(password) = server.password.as_ref().filter(|password| !password.is_empty()) {
+        args.push("+password".to_string());
+        args.push(password.clone());
+    }
+    args
+}
+
+fn encode_arg(arg: &str) -> String {
+    arg.bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b':' | b'+' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+// Steam hands everything between `//` and the trailing `/` to the game as arguments
+pub fn steam_run_url(args: &[String]) -> String {
+    let args: Vec<String> = args.iter().map(|arg| encode_arg(arg)).collect();
+    format!("steam://run/{}//{}/", VALHEIM_APP_ID, args.join("%20"))
+}
+
+fn open_url(url: &str) -> Result<(), LoaderError> {
+    use std::process::Command;
+
+    #[cfg(target_os = "windows")]
+    let (program, args) = ("cmd", vec!["/C", "start", "", url]);
+    #[cfg(target_os = "macos")]
+    let (program, args) = ("open", vec![url]);
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let (program, args) = ("xdg-open", vec![url]);
+
+    Command::new(program).args(args).spawn().at(Path::new(program))?;
+
+    Ok(())
+}
+
+// Starts Valheim through Steam, switching doorstop on or off first so the choice sticks for this launch
+pub fn play(valheim_path: &Path, server: Option<&ServerInfo>, connect: bool, modded: bool) -> Result<(), LoaderError> {
+    game::check_game_closed()?;
+
+    match disable::bepinex_enabled(valheim_path) {
+        Some(enabled) if enabled != modded => disable::set_bepinex_enabled(valheim_path, modded)?,
+        None if modded => return Err(LoaderError::BepInExMissing { path: valheim_path.join(disable::DOORSTOP_CONFIG) }),
+        _ => {}
+    }
+
+    let args = launch_args(server, connect);
+    // The password stays out of the log
+    tracing::info!(modded, connect = server.is_some() && connect, "Launching Valheim through Steam");
+    open_url(&steam_run_url(&args))?;
+
+    Ok(())
+}
+
+#[component]
+pub fn PlayPanel(
+    valheim_location: Signal<Option<PathBuf>>,
+    server_info: Signal<Option<ServerInfo>>,
+    status: Signal<String>,
+    disabled: bool,
+) -> Element {
+    let mut connect = use_signal(|| true);
+
+    let Some(valheim_path) = valheim_location() else { return rsx! {}; };
+
+    let mut launch = move |valheim_path: PathBuf, modded: bool| {
+        match play(&valheim_path, server_info.read().as_ref(), connect(), modded) {
+            Ok(_) if modded => { status.set("Starting modded Valheim through Steam...".to_string()); }
+            Ok(_) => { status.set("Starting vanilla Valheim through Steam...".to_string()); }
+            Err(e) => { status.set(format!("Could not start Valheim: {}", e.user_message())); }
+        }
+    };
+
+    rsx! {
+        div {
+            style: "display: flex; gap: 10px; margin-top: 10px;",
+            button {
+                style: "flex: 7; padding: 10px 20px; font-size: 14px; background-color: #5c7e10; color: white; border: none; border-radius: 5px; cursor: pointer;",
+                disabled: disabled,
+                onclick: {
+                    let valheim_path = valheim_path.clone();
+                    move |_| launch(valheim_path.clone(), true)
+                },
+                "Play"
+            }
+            button {
+                style: "flex: 3; padding: 10px 20px; font-size: 14px; background-color: #6c757d; color: white; border: none; border-radius: 5px; cursor: pointer;",
+                disabled: disabled,
+                onclick: {
+                    let valheim_path = valheim_path.clone();
+                    move |_| launch(valheim_path.clone(), false)
+                },
+                "Play Vanilla"
+            }
+        }
+        if let Some(server) = server_info() {
+            label {
+                style: "display: flex; align-items: center; gap: 5px; margin-top: 8px; font-size: 12px; color: #666;",
+                input {
+                    r#type: "checkbox",
+                    checked: connect(),
+                    onchange: move |e: FormEvent| connect.set(e.checked()),
+                }
+                "Join {server.address} on start"
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::Mod;
+
+const OTHER_SECTION: &str = "Other";
+// Pure dependencies that add nothing on their own; manifests can also mark them with `category: "library"`
+const KNOWN_LIBRARIES: [&str; 4] = ["BepInExPack", "BepInExPack_Valheim", "Jotunn", "Zen_ModLib"];
+
+pub fn is_library(mod_item: &Mod) -> bool {
+    KNOWN_LIBRARIES.contains(&mod_item.name.as_str())
+        || mod_item.category.as_deref().is_some_and(|category| category.eq_ignore_ascii_case("library") || category.eq_ignore_ascii_case("libraries"))
+}
+
+// The group names the section, the category stands in when there is none
+pub fn section_name(mod_item: &Mod) -> &str {
+    mod_item.group.as_deref()
+        .or(mod_item.category.as_deref())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(OTHER_SECTION)
+}
+
+// Every word of the filter has to appear in the name, description, group or category
+pub fn matches_filter(mod_item: &Mod, filter: &str) -> bool {
+    let haystack = format!(
+        "{} {} {} {}",
+        mod_item.name,
+        mod_item.description,
+        mod_item.group.as_deref().unwrap_or_default(),
+        mod_item.category.as_deref().unwrap_or_default()
+    ).to_lowercase();
+    filter.to_lowercase().split_whitespace().all(|word| haystack.contains(word))
+}
+
+// Sections in manifest order (the first mod of a section places it), "Other" last
+pub fn sections(mods: &[Mod], filter: &str, show_libraries: bool) -> Vec<(String, Vec<Mod>)> {
+    let mut sections: Vec<(String, Vec<Mod>)> = Vec::new();
+    for mod_item in mods.iter().filter(|m| (show_libraries || !is_library(m)) && matches_filter(m, filter)) {
+        let name = section_name(mod_item);
+        match sections.iter_mut().find(|(section, _)| section == name) {
+            Some((_, section_mods)) => section_mods.push(mod_item.clone()),
+            None => sections.push((name.to_string(), vec![mod_item.clone()])),
+        }
+    }
+
+    sections.sort_by_key(|(name, _)| name == OTHER_SECTION);
+    sections
+}
@@ -0,0 +1,259 @@
+use dioxus::prelude::*;
+use serde::Deserialize;
+use std::{io::Read, path::{Component, Path, PathBuf}};
+use crate::bepmod::Requirement;
+use crate::error::{LoaderError, PathContext};
+use crate::receipt;
+use crate::settings::Settings;
+use crate::{backup, game, update, Mod};
+
+// Thunderstore-style manifest.json, optional for local packages
+#[derive(Deserialize)]
+struct LocalManifest {
+    name: String,
+    version_number: String,
+    #[serde(default)]
+    description: String,
+}
+
+// A zip or folder picked from disk, described like a resolved package
+#[derive(Clone, PartialEq, Debug)]
+pub struct LocalPackage {
+    pub path: PathBuf,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    // Thunderstore layout, only the plugins folder is installed
+    pub has_plugins_dir: bool,
+}
+
+fn parse_manifest(content: &str) -> Option<LocalManifest> {
+    serde_json::from_str(content.trim_start_matches('\u{feff}')).ok()
+}
+
+pub fn read_local_package(path: &Path) -> Result<LocalPackage, LoaderError> {
+    let (manifest, has_plugins_dir) = if path.is_dir() {
+        let manifest = std::fs::read_to_string(path.join("manifest.json")).ok().and_then(|content| parse_manifest(&content));
+        (manifest, path.join("plugins").is_dir())
+    } else {
+        let archive_error = |source| LoaderError::Archive { path: path.to_path_buf(), source };
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).at(path)?).map_err(archive_error)?;
+        let has_plugins_dir = archive.file_names().any(|name| name.starts_with("plugins/"));
+        let manifest = match archive.by_name("manifest.json") {
+            Ok(mut file) => {
+                let mut content = String::new();
+                file.read_to_string(&mut content).at(path)?;
+                parse_manifest(&content)
+            }
+            Err(_) => None,
+        };
+        (manifest, has_plugins_dir)
+    };
+
+    let file_stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let package = match manifest {
+        Some(manifest) => LocalPackage {
+            path: path.to_path_buf(),
+            name: manifest.name,
+            version: manifest.version_number,
+            description: manifest.description,
+            has_plugins_dir,
+        },
+        None => LocalPackage {
+            path: path.to_path_buf(),
+            name: file_stem,
+            version: "local".to_string(),
+            description: String::new(),
+            has_plugins_dir,
+        },
+    };
+
+    // The name becomes a folder under plugins, it mustn't point anywhere else
+    let mut components = Path::new(&package.name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+        return Err(LoaderError::InvalidPackageName { name: package.name });
+    }
+    Ok(package)
+}
+
+impl LocalPackage {
+    pub fn to_mod(&self) -> Mod {
+        Mod {
+            id: format!("local-{}", self.name),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            icon_url: String::new(),
+            download_url: self.path.display().to_string(),
+            version: self.version.clone(),
+            enabled: true,
+            from: self.has_plugins_dir.then(|| "plugins".to_string()),
+            to: Some(PathBuf::from("BepInEx").join("plugins").join(&self.name).to_string_lossy().to_string()),
+            required_by_server: false,
+            sha256: None,
+            requirement: Requirement::Recommended,
+            dependencies: Vec::new(),
+            category: None,
+            group: Some("Local".to_string()),
+            conflicts_with: Vec::new(),
+            replaces: Vec::new(),
+            thunderstore: false,
+        }
+    }
+}
+
+// Folder counterpart of extracting an archive
+fn copy_package(source_dir: &Path, from_dir: Option<&str>, target_dir: &Path) -> Result<Vec<PathBuf>, LoaderError> {
+    let root = from_dir.map(|from| source_dir.join(from)).unwrap_or_else(|| source_dir.to_path_buf());
+    let mut written_files = Vec::new();
+
+    for file in backup::collect_files(&root).at(&root)? {
+        let Ok(relative) = file.strip_prefix(&root) else { continue; };
+        let outpath = target_dir.join(relative);
+        if let Some(p) = outpath.parent() {
+            std::fs::create_dir_all(p).at(p)?;
+        }
+        std::fs::copy(&file, &outpath).at(&outpath)?;
+        written_files.push(outpath);
+    }
+
+    Ok(written_files)
+}
+
+// Installs like any other package: files staged, old version out, files in, receipt written.
+// Local packages are never in the manifest, so they're kept instead of treated as orphans.
+pub fn install_local(valheim_path: &Path, package: &LocalPackage, settings: &mut Settings) -> Result<Mod, LoaderError> {
+    let mod_item = package.to_mod();
+    tracing::info!(path = %package.path.display(), package = %mod_item.id, version = %mod_item.version, "Installing local package");
+
+    // A broken archive or folder must not cost the installed version
+    let staging_dir = receipt::modloader_dir(valheim_path).join("staging").join(&mod_item.id);
+    if staging_dir.is_dir() {
+        std::fs::remove_dir_all(&staging_dir).at(&staging_dir)?;
+    }
+    let staged = if package.path.is_dir() {
+        copy_package(&package.path, mod_item.from.as_deref(), &staging_dir)
+    } else {
+        crate::extract_mod(&package.path, mod_item.from.clone(), &staging_dir)
+    };
+    let installed = staged.and_then(|_| {
+        if let Some(installed) = receipt::load_receipts(valheim_path).iter().find(|r| r.id == mod_item.id) {
+            update::remove_package(valheim_path, installed)?;
+        }
+        let target_dir = valheim_path.join(mod_item.to.clone().unwrap_or_default());
+        copy_package(&staging_dir, None, &target_dir)
+    });
+    if let Err(e) = std::fs::remove_dir_all(&staging_dir) {
+        tracing::debug!(dir = %staging_dir.display(), error = %e, "Could not remove staging folder");
+    }
+    receipt::record_install(valheim_path, &mod_item, &installed?)?;
+
+    if !settings.kept_packages.contains(&mod_item.id) {
+        settings.kept_packages.push(mod_item.id.clone());
+        settings.save()?;
+    }
+
+    Ok(mod_item)
+}
+
+fn pick_archive() -> Option<PathBuf> {
+    use rfd::FileDialog;
+
+    FileDialog::new()
+        .set_title("Select Mod Archive")
+        .add_filter("Zip archive", &["zip"])
+        .pick_file()
+}
+
+fn pick_folder() -> Option<PathBuf> {
+    use rfd::FileDialog;
+
+    FileDialog::new()
+        .set_title("Select Mod Folder")
+        .pick_folder()
+}
+
+#[component]
+pub fn LocalInstallPanel(valheim_location: Signal<Option<PathBuf>>, settings: Signal<Settings>, status: Signal<String>, disabled: bool) -> Element {
+    let mut dragging = use_signal(|| false);
+
+    let install_paths = move |paths: Vec<PathBuf>| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        spawn(async move {
+            if let Err(e) = game::ensure_game_closed(settings().wait_for_game_exit, status).await {
+                status.set(format!("Install cancelled: {}", e.user_message()));
+                return;
+            }
+            if let Err(e) = crate::backup_saves_if_enabled(&settings(), status) {
+                tracing::error!(error = %e, "Save backup failed, install cancelled");
+                status.set(format!("Error backing up saves, install cancelled: {}", e.user_message()));
+                return;
+            }
+            for path in paths {
+                let mut updated = settings();
+                let result = read_local_package(&path)
+                    .and_then(|package| install_local(&valheim_path, &package, &mut updated));
+                match result {
+                    Ok(mod_item) => { status.set(format!("Installed local package {} v{}", mod_item.name, mod_item.version)); }
+                    Err(e) => {
+                        tracing::error!(path = %path.display(), error = %e, "Could not install local package");
+                        status.set(format!("Error installing {}: {}", path.display(), e.user_message()));
+                    }
+                }
+                settings.set(updated);
+            }
+        });
+    };
+
+    let pick_zip = move |_| {
+        if let Some(path) = pick_archive() {
+            install_paths(vec![path]);
+        }
+    };
+    let pick_dir = move |_| {
+        if let Some(path) = pick_folder() {
+            install_paths(vec![path]);
+        }
+    };
+
+    rsx! {
+        div {
+            style: if dragging() {
+                "background: #eef6e0; border: 2px dashed #5c7e10; border-radius: 5px; padding: 15px; margin-top: 20px; text-align: center;"
+            } else {
+                "background: white; border: 2px dashed #ddd; border-radius: 5px; padding: 15px; margin-top: 20px; text-align: center;"
+            },
+            ondragover: move |e| {
+                e.prevent_default();
+                dragging.set(true);
+            },
+            ondragleave: move |_| dragging.set(false),
+            ondrop: move |e| {
+                e.prevent_default();
+                dragging.set(false);
+                if !disabled {
+                    install_paths(e.data_transfer().files().iter().map(|file| file.path()).collect());
+                }
+            },
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "Local Mods"
+            }
+            p {
+                style: "margin: 0 0 10px 0; font-size: 12px; color: #999;",
+                "Drop a mod .zip or folder here"
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-right: 5px;",
+                disabled: disabled,
+                onclick: pick_zip,
+                "Choose Zip..."
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                disabled: disabled,
+                onclick: pick_dir,
+                "Choose Folder..."
+            }
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use dioxus::prelude::*;
+use std::{collections::HashMap, io::{Read, Seek, SeekFrom}, path::{Path, PathBuf}, time::Duration};
+use crate::{backup, plugins};
+
+// Only the end of the log is read, Valheim can write megabytes per session
+const TAIL_BYTES: u64 = 512 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    Fatal,
+    Error,
+    Warning,
+    Message,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 6] = [LogLevel::Fatal, LogLevel::Error, LogLevel::Warning, LogLevel::Message, LogLevel::Info, LogLevel::Debug];
+
+    fn parse(value: &str) -> Option<LogLevel> {
+        LogLevel::ALL.into_iter().find(|level| level.label().eq_ignore_ascii_case(value.trim()))
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Fatal => "Fatal",
+            LogLevel::Error => "Error",
+            LogLevel::Warning => "Warning",
+            LogLevel::Message => "Message",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            LogLevel::Fatal | LogLevel::Error => "#800000",
+            LogLevel::Warning => "#b8860b",
+            LogLevel::Message => "#1b2838",
+            LogLevel::Info | LogLevel::Debug => "#666",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub source: String,
+    pub message: String,
+    pub stack: Vec<String>,
+}
+
+impl LogEntry {
+    pub fn is_exception(&self) -> bool {
+        self.message.contains("Exception") || !self.stack.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LogFile {
+    BepInEx,
+    Unity,
+}
+
+impl LogFile {
+    pub fn path(self, valheim_path: &Path) -> Option<PathBuf> {
+        match self {
+            LogFile::BepInEx => Some(valheim_path.join("BepInEx").join("LogOutput.log")),
+            LogFile::Unity => backup::local_valheim_dirs()
+                .into_iter()
+                .map(|location| location.path.join("Player.log"))
+                .find(|path| path.is_file()),
+        }
+    }
+}
+
+pub fn read_tail(path: &Path) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let length = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(length.saturating_sub(TAIL_BYTES))).ok()?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    let content = String::from_utf8_lossy(&bytes).to_string();
+
+    // Drop the partial first line when starting mid-file
+    if length > TAIL_BYTES {
+        return content.split_once('\n').map(|(_, rest)| rest.to_string());
+    }
+    Some(content)
+}
+
+// BepInEx lines look like "[Warning:   Jotunn] message"; lines without that
+// header belong to the previous entry (stack traces, multi-line messages)
+pub fn parse_bepinex_log(content: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    for line in content.lines() {
+        let header = line.strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .and_then(|(header, message)| {
+                let (level, source) = header.split_once(':')?;
+                Some((LogLevel::parse(level)?, source.trim(), message.trim_start()))
+            });
+
+        match header {
+            Some((level, source, message)) => entries.push(LogEntry {
+                level,
+                source: source.to_string(),
+                message: message.to_string(),
+                stack: Vec::new(),
+            }),
+            None if line.trim().is_empty() => {}
+            None => match entries.last_mut() {
+                Some(last) => last.stack.push(line.to_string()),
+                None => entries.push(LogEntry {
+                    level: LogLevel::Info,
+                    source: String::new(),
+                    message: line.to_string(),
+                    stack: Vec::new(),
+                }),
+            },
+        }
+    }
+
+    entries
+}
+
+// Unity's Player.log has no levels, exceptions are followed by indented stack frames
+pub fn parse_unity_log(content: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let is_frame = line.starts_with(' ') || line.starts_with('\t') || line.starts_with("(Filename:");
+        if is_frame && let Some(last) = entries.last_mut() {
+            last.stack.push(line.to_string());
+            continue;
+        }
+
+        let level = if line.contains("Exception") || line.starts_with("Error") {
+            LogLevel::Error
+        } else if line.starts_with("Warning") {
+            LogLevel::Warning
+        } else {
+            LogLevel::Info
+        };
+        entries.push(LogEntry {
+            level,
+            source: "Unity".to_string(),
+            message: line.to_string(),
+            stack: Vec::new(),
+        });
+    }
+
+    entries
+}
+
+pub fn read_log(valheim_path: &Path, log_file: LogFile) -> Vec<LogEntry> {
+    let Some(content) = log_file.path(valheim_path).and_then(|path| read_tail(&path)) else { return Vec::new(); };
+    match log_file {
+        LogFile::BepInEx => parse_bepinex_log(&content),
+        LogFile::Unity => parse_unity_log(&content),
+    }
+}
+
+// BepInEx logs under the plugin's name, which leads back to the package that installed it
+pub fn plugin_owners(valheim_path: &Path) -> HashMap<String, String> {
+    plugins::scan_plugins(valheim_path)
+        .into_iter()
+        .filter_map(|plugin| Some((plugin.name, plugin.package?)))
+        .collect()
+}
+
+#[component]
+pub fn LogViewer(valheim_location: Signal<Option<PathBuf>>) -> Element {
+    let mut expanded = use_signal(|| false);
+    let mut log_file = use_signal(|| LogFile::BepInEx);
+    let mut entries = use_signal(Vec::<LogEntry>::new);
+    let mut owners = use_signal(HashMap::<String, String>::new);
+    let mut max_level = use_signal(|| LogLevel::Info);
+    let mut source_filter = use_signal(String::new);
+
+    // Tail the selected log while the viewer is open
+    use_future(move || async move {
+        loop {
+            if expanded() && let Some(valheim_path) = valheim_location() {
+                entries.set(read_log(&valheim_path, log_file()));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    let toggle = move |_| {
+        if !expanded() && let Some(valheim_path) = valheim_location() {
+            owners.set(plugin_owners(&valheim_path));
+            entries.set(read_log(&valheim_path, log_file()));
+        }
+        expanded.set(!expanded());
+    };
+
+    let mut sources: Vec<String> = entries.read().iter().map(|entry| entry.source.clone()).collect();
+    sources.sort();
+    sources.dedup();
+
+    let visible: Vec<LogEntry> = entries.read().iter()
+        .filter(|entry| entry.level <= max_level())
+        .filter(|entry| source_filter.read().is_empty() || entry.source == *source_filter.read())
+        .cloned()
+        .collect();
+    let error_count = entries.read().iter().filter(|entry| entry.level <= LogLevel::Error).count();
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin: 20px 0;",
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center; cursor: pointer;",
+                onclick: toggle,
+                h2 {
+                    style: "margin: 0; font-size: 16px; color: #1b2838;",
+                    "Game Logs"
+                    if expanded() && error_count > 0 {
+                        span { style: "color: #800000; font-size: 13px; font-weight: normal;", " ({error_count} errors)" }
+                    }
+                }
+                span { style: "color: #999; font-size: 12px;", if expanded() { "▲" } else { "▼" } }
+            }
+            if expanded() {
+                div {
+                    style: "display: flex; gap: 10px; margin: 10px 0; font-size: 13px;",
+                    select {
+                        onchange: move |e: FormEvent| {
+                            log_file.set(if e.value() == "unity" { LogFile::Unity } else { LogFile::BepInEx });
+                            source_filter.set(String::new());
+                            if let Some(valheim_path) = valheim_location() {
+                                entries.set(read_log(&valheim_path, log_file()));
+                            }
+                        },
+                        option { value: "bepinex", selected: log_file() == LogFile::BepInEx, "LogOutput.log" }
+                        option { value: "unity", selected: log_file() == LogFile::Unity, "Player.log" }
+                    }
+                    select {
+                        onchange: move |e: FormEvent| {
+                            if let Some(level) = LogLevel::parse(&e.value()) {
+                                max_level.set(level);
+                            }
+                        },
+                        for level in LogLevel::ALL {
+                            option { value: "{level.label()}", selected: level == max_level(), "{level.label()} and above" }
+                        }
+                    }
+                    select {
+                        onchange: move |e: FormEvent| source_filter.set(e.value()),
+                        option { value: "", selected: source_filter.read().is_empty(), "All sources" }
+                        for source in sources {
+                            option { value: "{source}", selected: *source_filter.read() == source, "{source}" }
+                        }
+                    }
+                }
+                div {
+                    style: "max-height: 300px; overflow-y: auto; font-family: monospace; font-size: 12px; background: #fafafa; border: 1px solid #eee; padding: 5px;",
+                    if visible.is_empty() {
+                        p { style: "margin: 0; color: #999;", "No log entries." }
+                    }
+                    for (index, entry) in visible.iter().enumerate() {
+                        div {
+                            key: "{index}",
+                            style: if entry.is_exception() { "padding: 2px 0; border-bottom: 1px solid #eee; background: #fdecea;" } else { "padding: 2px 0; border-bottom: 1px solid #eee;" },
+                            span { style: "color: {entry.level.color()}; font-weight: bold;", "[{entry.level.label()}] " }
+                            if !entry.source.is_empty() {
+                                span { style: "color: #999;", "{entry.source}" }
+                                if let Some(package) = owners.read().get(&entry.source) {
+                                    span { style: "color: #999;", " ({package})" }
+                                }
+                                ": "
+                            }
+                            "{entry.message}"
+                            if !entry.stack.is_empty() {
+                                pre {
+                                    style: "margin: 2px 0 0 20px; white-space: pre-wrap; color: #800000;",
+                                    {entry.stack.join("\n")}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,43 @@
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 use core::time;
-use std::{path::PathBuf, process, time::Duration};
+use std::{path::{Path, PathBuf}, process, time::Duration};
+mod activity;
+mod backup;
 mod bepmod;
+mod catalog;
+mod collision;
+mod compat;
+mod configs;
+mod conflicts;
+mod details;
+mod detect;
+mod disable;
+mod dotnet;
+mod error;
+mod game;
+mod launch;
+mod local;
+mod listing;
+mod logs;
+mod modpack;
+mod plugins;
+mod r2modman;
+mod receipt;
+mod selection;
+mod server;
+mod settings;
+mod source;
+mod support;
+mod update;
+use error::{LoaderError, PathContext};
 use dioxus::desktop::{Config, WindowBuilder};
 
 const MODS_JSON_URL: &'static str = "https://raw.githubusercontent.com/IdotNuerk/CumHeim/master/mods.json";
 
 fn main() {
+    activity::init_logging();
+
     let icon_path = std::path::PathBuf::from("icons/icon.ico");
     let icon_bytes = std::fs::read(&icon_path).expect("Failed to read icon file");
     let icon_image = image::load_from_memory(&icon_bytes)
@@ -50,6 +80,19 @@ struct Mod {
     enabled: bool,
     from: Option<String>,
     to: Option<String>,
+    // The manifest pins this exact version because the server runs it
+    required_by_server: bool,
+    // Checked against the downloaded archive when the manifest provides it
+    sha256: Option<String>,
+    requirement: bepmod::Requirement,
+    // Thunderstore references ("Namespace-Name-1.0.0") of the packages this one needs
+    dependencies: Vec<String>,
+    category: Option<String>,
+    group: Option<String>,
+    conflicts_with: Vec<bepmod::ConflictEntry>,
+    replaces: Vec<String>,
+    // Only Thunderstore packages have a README, changelog and version history
+    thunderstore: bool,
 }
 
 #[derive(Clone, PartialEq, Deserialize, Serialize, Debug)]
@@ -83,10 +126,22 @@ fn app() -> Element {
     let mut mods = use_signal(|| Vec::<Mod>::new());
     let mut loading_mods = use_signal(|| false);
     let mut mods_json_info = use_signal(|| Vec::<bepmod::BepinexMod>::new() );
+    let mut server_info = use_signal(|| None::<bepmod::ServerInfo>);
+    let mut config_presets = use_signal(Vec::<bepmod::ConfigPreset>::new);
+    let mut modpack_info = use_signal(|| None::<source::ModpackInfo>);
+    // False while loading or when part of the manifest (the modpack) couldn't be fetched
+    let mut manifest_complete = use_signal(|| false);
+    let mut browse_tab = use_signal(|| false);
+    let mut details_for = use_signal(|| None::<Mod>);
+    let mut mod_filter = use_signal(String::new);
+    let mut show_libraries = use_signal(|| false);
+    let mut collapsed_sections = use_signal(Vec::<String>::new);
+    let mut collisions = use_signal(Vec::<collision::Collision>::new);
     let mut primary_pressed = use_signal(|| false);
     let mut secondary_pressed = use_signal(|| false);
     let mut install_is_processing = use_signal(|| false);
     let mut uninstall_is_processing = use_signal(|| false);
+    let mut settings = use_signal(settings::Settings::load);
     
     // Find Steam on component mount
     use_effect(move || {
@@ -121,10 +176,22 @@ fn app() -> Element {
 
         spawn(async move {
             uninstall_is_processing.set(true);
+            if let Err(e) = game::ensure_game_closed(settings().wait_for_game_exit, status).await {
+                status.set(format!("Uninstall cancelled: {}", e.user_message()));
+                uninstall_is_processing.set(false);
+                return;
+            }
+            if let Err(e) = backup_saves_if_enabled(&settings(), status) {
+                tracing::error!(error = %e, "Save backup failed, uninstall cancelled");
+                status.set(format!("Error backing up saves, uninstall cancelled: {}", e.user_message()));
+                uninstall_is_processing.set(false);
+                return;
+            }
             match uninstall(valheim_location()) {
                 Ok(_) => { status.set("Finished uninstalling all mods.".to_string()); },
                 Err(e) => {
-                    status.set(format!("Error uninstalling all mods: {}", e));
+                    tracing::error!(error = %e, "Uninstall failed");
+                    status.set(format!("Error uninstalling all mods: {}", e.user_message()));
                 }
             }
             uninstall_is_processing.set(false);
@@ -134,56 +201,81 @@ fn app() -> Element {
     use_effect( move || {
         spawn(async move {
             loading_mods.set(true);
+            manifest_complete.set(false);
             status.set("Fetching mod information from Thunderstore...".to_string());
             
             let mut fetched_mods = Vec::new();
+            let personal_ids: Vec<String>;
+            let mut complete = true;
+
+            match get_mods_json().await {
+                Ok(manifest) => {
+                    let (mut manifest_mods, server, modpack_ref, presets) = manifest.into_parts();
+                    server_info.set(server);
+                    config_presets.set(presets);
 
-            if let Ok(mods) = get_mods_json().await {
-                mods_json_info.set(mods);
+                    // A modpack expands into its dependencies, entries listed next to it win
+                    if let Some(reference) = modpack_ref {
+                        status.set(format!("Fetching modpack {}...", reference));
+                        match source::resolve_modpack(&reference).await {
+                            Ok((pack, entries)) => {
+                                tracing::info!(modpack = %pack.id, version = %pack.version, mods = entries.len(), "Expanded modpack");
+                                let listed: Vec<String> = manifest_mods.iter().map(|entry| entry.package_id()).collect();
+                                manifest_mods.extend(entries.into_iter().filter(|entry| !listed.contains(&entry.package_id())));
+                                modpack_info.set(Some(pack));
+                            }
+                            Err(e) => {
+                                tracing::error!(modpack = %reference, error = %e, "Could not fetch modpack");
+                                status.set(format!("Error fetching modpack {}: {}", reference, e.user_message()));
+                                complete = false;
+                            }
+                        }
+                    }
+
+                    // Personal mods come last, the server's entries for the same package win
+                    let listed: Vec<String> = manifest_mods.iter().map(|entry| entry.package_id()).collect();
+                    let personal: Vec<bepmod::BepinexMod> = settings().personal_mods.iter()
+                        .filter_map(|reference| catalog::personal_entry(reference))
+                        .filter(|entry| !listed.contains(&entry.package_id()))
+                        .collect();
+                    personal_ids = personal.iter().map(|entry| entry.package_id()).collect();
+                    manifest_mods.extend(personal);
+                    mods_json_info.set(manifest_mods);
+                    manifest_complete.set(complete);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not fetch mod manifest");
+                    status.set(format!("Error fetching the mod list: {}", e.user_message()));
+                    loading_mods.set(false);
+                    return;
+                }
             }
             
             for info in mods_json_info.iter() {
-                let name = info.name.trim();
-                let namespace = info.namespace.trim();
-                if name.is_empty() || namespace.is_empty() {
+                if !info.is_valid() {
                     continue;
                 }
-                
-                // Fetch from Thunderstore API
-                let api_url = format!("https://thunderstore.io/api/experimental/package/{}/{}/", namespace, name);
-                
-                match reqwest::get(&api_url).await {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            match response.json::<ThunderstorePackage>().await {
-                                Ok(package) => {
-                                    fetched_mods.push(Mod {
-                                        id: package.full_name.clone(),
-                                        name: package.name.clone(),
-                                        description: package.latest.description.clone(),
-                                        icon_url: package.latest.icon.clone(),
-                                        download_url: package.latest.download_url.clone(),
-                                        version: package.latest.version_number.clone(),
-                                        enabled: true,
-                                        from: info.from.clone(),
-                                        to: info.to.clone(),
-                                    });
-                                    status.set(format!("Loaded: {} v{}", package.name, package.latest.version_number));
-                                }
-                                Err(e) => {
-                                    status.set(format!("Error parsing {}: {}", name, e));
-                                }
-                            }
-                        } else {
-                            status.set(format!("Could not find mod: {}", name));
-                        }
+                let name = info.name.trim();
+
+                match source::resolve(&info).await {
+                    Ok(mut mod_item) => {
+                        // A personal mod's version comes from the profile it was imported from, not the server
+                        mod_item.required_by_server &= !personal_ids.contains(&mod_item.id);
+                        status.set(format!("Loaded: {} v{}", mod_item.name, mod_item.version));
+                        fetched_mods.push(mod_item);
+                    }
+                    Err(LoaderError::HttpStatus { status: http_status, .. }) if http_status == reqwest::StatusCode::NOT_FOUND => {
+                        tracing::warn!(package = name, "Package not found");
+                        status.set(format!("Could not find mod: {}", name));
                     }
                     Err(e) => {
-                        status.set(format!("Error fetching {}: {}", name, e));
+                        tracing::warn!(package = name, error = %e, "Could not fetch package");
+                        status.set(format!("Error fetching {}: {}", name, e.user_message()));
                     }
                 }
             }
             
+            selection::complete(&mut fetched_mods);
             mods.set(fetched_mods.clone());
             loading_mods.set(false);
             
@@ -196,116 +288,257 @@ fn app() -> Element {
     });
 
     let mut toggle_mod = move |mod_id: String| {
-        mods.write().iter_mut().for_each(|m| {
-            if m.id == mod_id {
-                m.enabled = !m.enabled;
-            }
-        });
+        match selection::toggle(&mut mods.write(), &mod_id) {
+            Ok(Some(message)) => status.set(message),
+            Ok(None) => {}
+            Err(reason) => status.set(reason),
+        }
     };
     
     let select_all = move |_| {
-        mods.write().iter_mut().for_each(|m| m.enabled = true);
+        let refused = selection::select_all(&mut mods.write());
+        if !refused.is_empty() {
+            status.set(format!("Selected all mods except {} that can't be combined. {}", refused.len(), refused.join(" ")));
+        }
     };
     
     let deselect_all = move |_| {
-        mods.write().iter_mut().for_each(|m| m.enabled = false);
+        selection::deselect_all(&mut mods.write());
+        status.set("Deselected all mods except required ones and their dependencies.".to_string());
     };
     
-    let download_to_steamapps = move |_| {
+    let mut apply_changes = move || {
         if install_is_processing() {
             return; // Don't process if already processing
         }
 
-        let selected_mods: Vec<Mod> = mods.read().iter()
-            .filter(|m| m.enabled)
-            .cloned()
+        let Some(existing_valheim_dir) = valheim_location() else {
+            status.set("Valheim installation not found when trying to install mods.".to_string());
+            return;
+        };
+
+        if let Some(conflict) = conflicts::find_conflicts(&mods.read()).first() {
+            status.set(format!("Can't install, {}", conflict));
+            return;
+        }
+
+        if detect::has_foreign_bepinex(&existing_valheim_dir) {
+            status.set("An existing BepInEx install was found. Adopt, back up or replace it before installing.".to_string());
+            return;
+        }
+
+        let all_mods: Vec<Mod> = mods.read().clone();
+        // The pack only counts as installed when none of its pinned mods were left out
+        let applied_modpack = modpack_info().filter(|_| all_mods.iter().all(|m| !m.required_by_server || m.enabled));
+        let changes: Vec<update::PlanEntry> = update::build_plan(&existing_valheim_dir, &all_mods, manifest_ids(&mods_json_info.read(), manifest_complete()).as_deref(), &receipt::load_receipts(&existing_valheim_dir), &settings().kept_packages)
+            .into_iter()
+            .filter(|entry| entry.is_change())
             .collect();
         
-        if selected_mods.is_empty() {
-            status.set("Please select at least one mod to install".to_string());
+        if changes.is_empty() {
+            if let Some(pack) = &applied_modpack {
+                modpack::record_applied(&existing_valheim_dir, pack);
+            }
+            status.set("All selected mods are already up to date.".to_string());
             return;
         }
 
-        if let Some(bepinex) = selected_mods.iter().find(|sel_mod| sel_mod.name == "BepInExPack") {
-            let existing_valheim_dir = valheim_location();
-            let bepinex_clone = bepinex.clone();
-            spawn(async move {
-                install_is_processing.set(true);
-                let target_dir = PathBuf::from(existing_valheim_dir.clone().unwrap()).join(bepinex_clone.to.unwrap_or_default().clone());
-                let mut installed_count = 0;
-                let total_mods = selected_mods.len();
-
-                match download_and_extract_mod(&bepinex_clone.download_url, bepinex_clone.from.clone(), &target_dir).await {
-                    Ok(_) => {
-                        installed_count += 1;
-                        status.set(format!("Installed BepInEx: v{}", bepinex_clone.version));
-                        
-                        if existing_valheim_dir.is_none() {
-                            status.set("Valheim installation not found when trying to install mods.".to_string());
-                            return;
-                        }
+        spawn(async move {
+            install_is_processing.set(true);
+            if let Err(e) = game::ensure_game_closed(settings().wait_for_game_exit, status).await {
+                status.set(format!("Install cancelled: {}", e.user_message()));
+                install_is_processing.set(false);
+                return;
+            }
+            if let Err(e) = backup_saves_if_enabled(&settings(), status) {
+                tracing::error!(error = %e, "Save backup failed, install cancelled");
+                status.set(format!("Error backing up saves, install cancelled: {}", e.user_message()));
+                install_is_processing.set(false);
+                return;
+            }
+            let mut receipts = receipt::load_receipts(&existing_valheim_dir);
+            // Only a fresh BepInEx needs the first run that generates its folders
+            let fresh_bepinex = !existing_valheim_dir.join("BepInEx").join("plugins").is_dir();
+            let mut applied_count = 0;
+            let total_changes = changes.len();
 
-                        let valheim_exe = target_dir.join("valheim.exe");
-                        status.set("Starting Valheim with BepInEx".to_string());
-                        let valheim_proc = process::Command::new(valheim_exe).spawn();
-                        match valheim_proc {
-                            Ok(mut child) => {
-                                let plugins_dir = target_dir.join("BepInEx").join("plugins");
-                                let max_time = std::time::Duration::from_secs(300);
-                                let start = std::time::Instant::now();
-                                while !plugins_dir.is_dir() {
-                                    if std::time::Instant::now() - start > max_time {
-                                        break;
-                                    }
-                                    std::thread::sleep(time::Duration::from_secs(1));
-                                }
-                                
-                                match child.kill() {
-                                    Ok(..) => { status.set("Successfully closed Valheim".to_string()); }
-                                    Err(e) => { status.set(format!("Error trying to close Valheim: {}", e)); }
-                                }
-                            }
-                            Err(e) => { 
-                                status.set(format!("Error starting Valheim with BepInEx: {}", e)); 
-                            }
+            // Everything is downloaded up front so file collisions are known before anything is written
+            let replaced: Vec<String> = changes.iter().map(|entry| entry.id.clone()).collect();
+            let mut planned: Vec<collision::PlannedPackage> = Vec::new();
+            for (index, entry) in changes.iter().filter(|entry| !entry.is_removal() && !bepmod::is_bepinex_pack(&entry.name)).enumerate() {
+                let Some(mod_item) = all_mods.iter().find(|m| m.id == entry.id) else { continue; };
+                status.set(format!("Downloading {}/{}: {} v{}...", index + 1, total_changes, mod_item.name, mod_item.version));
+                let archive = std::env::temp_dir().join(format!("cumheim-{}.zip", mod_item.id));
+                let result = match download_mod(&mod_item.download_url, mod_item.sha256.as_deref(), &archive).await {
+                    Ok(()) => collision::plan_package(&existing_valheim_dir, mod_item, &archive),
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(package) => planned.push(package),
+                    Err(e) => {
+                        tracing::error!(package = %mod_item.id, error = %e, "Could not download package");
+                        status.set(format!("Error downloading {}: {}", mod_item.name, e.user_message()));
+                        collision::remove_archives(&planned);
+                        install_is_processing.set(false);
+                        return;
+                    }
+                }
+            }
+            match collision::resolve(&existing_valheim_dir, &mut planned, &receipts, &replaced, settings().collision_policy) {
+                Ok(found) => collisions.set(found),
+                Err(e) => {
+                    tracing::error!(error = %e, "Install stopped by a file collision");
+                    status.set(format!("Install cancelled: {}", e.user_message()));
+                    collision::remove_archives(&planned);
+                    install_is_processing.set(false);
+                    return;
+                }
+            }
+
+            // BepInEx goes first so its folder layout exists before any plugin is extracted
+            if let Some(bepinex) = changes.iter()
+                .find(|entry| bepmod::is_bepinex_pack(&entry.name))
+                .and_then(|entry| all_mods.iter().find(|m| m.id == entry.id))
+            {
+                let target_dir = existing_valheim_dir.join(bepinex.to.clone().unwrap_or_default());
+                match download_and_extract_mod(&bepinex.download_url, bepinex.sha256.as_deref(), bepinex.from.clone(), &target_dir).await {
+                    Ok(files) => {
+                        if let Err(e) = receipt::record_install(&existing_valheim_dir, bepinex, &files) {
+                            tracing::error!(package = "BepInExPack", error = %e, "Could not write install receipt");
+                            status.set(format!("Error writing install receipt for BepInEx: {}", e));
                         }
-                        
-                        // Download and extract each mod after bepinex
-                        for mod_item in selected_mods {
-                            if mod_item.name == "BepInExPack" { 
-                                continue;
-                            }
+                        applied_count += 1;
+                        status.set(format!("Installed BepInEx: v{}", bepinex.version));
+                        if fresh_bepinex {
+                            first_run_bepinex(&target_dir, status);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!(error = %e, "Could not install BepInEx");
+                        status.set(format!("Error installing BepInEx: {}", e.user_message()));
+                        collision::remove_archives(&planned);
+                        install_is_processing.set(false);
+                        return;
+                    }
+                }
+            } else if !existing_valheim_dir.join("BepInEx").join("core").is_dir() {
+                status.set("BepInExPack must be selected to install mods.".to_string());
+                collision::remove_archives(&planned);
+                install_is_processing.set(false);
+                return;
+            }
 
-                            status.set(format!("Downloading {}/{}: {} v{}...", installed_count + 1, total_mods, mod_item.name, mod_item.version));
+            // Apply the remaining changes after bepinex
+            for entry in changes {
+                if bepmod::is_bepinex_pack(&entry.name) {
+                    continue;
+                }
 
-                            let internal_from_dir = mod_item.from;
-                            let target_dir = PathBuf::from(existing_valheim_dir.clone().unwrap()).join(mod_item.to.unwrap_or_default());
-                            
-                            match download_and_extract_mod(&mod_item.download_url, internal_from_dir, &target_dir).await {
-                                Ok(_) => {
-                                    installed_count += 1;
-                                    status.set(format!("Installed {}/{}: {} v{}", installed_count, total_mods, mod_item.name, mod_item.version));
-                                }
-                                Err(e) => {
-                                    status.set(format!("Error installing {}: {}", mod_item.name, e));
-                                    return;
-                                }
-                            }
+                let installed = receipts.iter().find(|r| r.id == entry.id).cloned();
+                if entry.is_removal() {
+                    status.set(format!("Removing {}/{}: {}...", applied_count + 1, total_changes, entry.name));
+                    if let Some(installed) = &installed && let Err(e) = update::remove_package(&existing_valheim_dir, installed) {
+                        tracing::error!(package = %entry.id, error = %e, "Could not remove package");
+                        status.set(format!("Error removing {}: {}", entry.name, e));
+                        collision::remove_archives(&planned);
+                        install_is_processing.set(false);
+                        return;
+                    }
+                    applied_count += 1;
+                    continue;
+                }
+
+                let Some(mod_item) = all_mods.iter().find(|m| m.id == entry.id) else { continue; };
+                let Some(package) = planned.iter().find(|package| package.id == mod_item.id) else { continue; };
+                status.set(format!("Installing {}/{}: {} v{}...", applied_count + 1, total_changes, mod_item.name, mod_item.version));
+
+                // Drop the previous version first so renamed files don't linger
+                if let Some(installed) = &installed && let Err(e) = update::remove_package(&existing_valheim_dir, installed) {
+                    tracing::error!(package = %mod_item.id, error = %e, "Could not remove old version");
+                    status.set(format!("Error removing old version of {}: {}", mod_item.name, e));
+                    collision::remove_archives(&planned);
+                    install_is_processing.set(false);
+                    return;
+                }
+
+                let extracted = collision::extract_planned(&existing_valheim_dir, package);
+                collision::remove_archives(std::slice::from_ref(package));
+                match extracted {
+                    Ok(files) => {
+                        if let Err(e) = collision::hand_over(&existing_valheim_dir, &mut receipts, package) {
+                            tracing::error!(package = %mod_item.id, error = %e, "Could not update the receipts of overwritten files");
+                            status.set(format!("Error updating install receipts for {}: {}", mod_item.name, e));
+                        }
+                        if let Err(e) = receipt::record_install(&existing_valheim_dir, mod_item, &files) {
+                            tracing::error!(package = %mod_item.id, error = %e, "Could not write install receipt");
+                            status.set(format!("Error writing install receipt for {}: {}", mod_item.name, e));
                         }
-                        
-                        status.set(format!("Installation complete! {} mod(s) installed successfully.", installed_count));
+                        applied_count += 1;
+                        status.set(format!("Installed {}/{}: {} v{}", applied_count, total_changes, mod_item.name, mod_item.version));
                     }
                     Err(e) => {
-                        status.set(format!("Error installing BepInEx: {}", e));
+                        tracing::error!(package = %mod_item.id, error = %e, "Could not install package");
+                        status.set(format!("Error installing {}: {}", mod_item.name, e.user_message()));
+                        collision::remove_archives(&planned);
+                        install_is_processing.set(false);
                         return;
                     }
                 }
-                install_is_processing.set(false);
-            });
+            }
+            
+            tracing::info!(changes = applied_count, "Update complete");
+            if let Some(pack) = &applied_modpack {
+                modpack::record_applied(&existing_valheim_dir, pack);
+            }
+            status.set(format!("Update complete! {} change(s) applied.", applied_count));
+            install_is_processing.set(false);
+        });
+    };
+
+    let download_to_steamapps = move |_| apply_changes();
+
+    // Receipts change through installs, uninstalls and the panels, which all report
+    // through `status`, so the plan is only rebuilt then or when the selection changes
+    let plan = use_memo(move || {
+        status.read();
+        let _ = (install_is_processing(), uninstall_is_processing());
+        valheim_location()
+            .map(|path| update::build_plan(&path, &mods.read(), manifest_ids(&mods_json_info.read(), manifest_complete()).as_deref(), &receipt::load_receipts(&path), &settings().kept_packages))
+            .unwrap_or_default()
+    });
+
+    // Selects and re-enables everything the server requires, then applies the plan,
+    // which installs/updates to the pinned versions and removes dropped packages
+    let sync_to_server = move |_| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        if let Err(e) = game::check_game_closed() {
+            status.set(e.user_message());
+            return;
+        }
+
+        let required: Vec<String> = mods.read().iter().filter(|m| m.required_by_server).map(|m| m.id.clone()).collect();
+        {
+            // Selecting pulls in dependencies, completing drops whatever the server's mods replace
+            let mut mods = mods.write();
+            for id in &required {
+                selection::select(&mut mods, id);
+            }
+            selection::complete(&mut mods);
+        }
+        for installed in receipt::load_receipts(&valheim_path).iter().filter(|r| r.disabled && required.contains(&r.id)) {
+            if let Err(e) = disable::enable_mod(&valheim_path, installed) {
+                status.set(format!("Error enabling {}: {}", installed.name, e));
+                return;
+            }
         }
+
+        apply_changes();
     };
     
     let enabled_count = mods.read().iter().filter(|m| m.enabled).count();
+    let sections = listing::sections(&mods.read(), &mod_filter(), show_libraries());
+    let plan = plan();
+    let change_count = plan.iter().filter(|entry| entry.is_change()).count();
     let primary_style = if primary_pressed() {
         "flex: 7; padding: 15px 30px; font-size: 16px; background-color: #0056b3; color: white; border: none; border-radius: 5px; cursor: pointer; transition: all 0.1s ease; transform: scale(0.95); box-shadow: inset 0 2px 4px rgba(0,0,0,0.2);"
     } else {
@@ -349,9 +582,17 @@ fn app() -> Element {
                     strong { "Status: " }
                     "{status}"
                 }
+                game::WaitBanner {}
             }
             
             if valheim_location().is_some() {
+                detect::ExistingInstallPanel {
+                    valheim_location,
+                    mods,
+                    status,
+                    disabled: install_is_processing() || uninstall_is_processing(),
+                }
+
                 div {
                     style: "margin-top: 20px;",
                     p {
@@ -370,61 +611,142 @@ fn app() -> Element {
                                 style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 20px; margin: 20px 0;",
                                 
                                 div {
-                                    style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 15px;",
-                                    h2 {
-                                        style: "margin: 0; font-size: 18px; color: #1b2838;",
-                                        "Mods to Install ({enabled_count} selected)"
+                                    style: "display: flex; gap: 4px; border-bottom: 2px solid #1b2838; margin-bottom: 15px;",
+                                    button {
+                                        style: if browse_tab() { "background: #e0e0e0; color: #1b2838; padding: 6px 14px; border: none; border-radius: 3px 3px 0 0; cursor: pointer; font-size: 13px;" } else { "background: #1b2838; color: white; padding: 6px 14px; border: none; border-radius: 3px 3px 0 0; cursor: pointer; font-size: 13px;" },
+                                        onclick: move |_| browse_tab.set(false),
+                                        "Modpack"
+                                    }
+                                    button {
+                                        style: if browse_tab() { "background: #1b2838; color: white; padding: 6px 14px; border: none; border-radius: 3px 3px 0 0; cursor: pointer; font-size: 13px;" } else { "background: #e0e0e0; color: #1b2838; padding: 6px 14px; border: none; border-radius: 3px 3px 0 0; cursor: pointer; font-size: 13px;" },
+                                        onclick: move |_| browse_tab.set(true),
+                                        "Browse"
                                     }
+                                }
+
+                                if browse_tab() {
+                                    catalog::BrowsePanel { mods, mods_json_info, settings, status }
+                                } else {
                                     div {
-                                        button {
-                                            style: "background: #5c7e10; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-right: 5px;",
-                                            onclick: select_all,
-                                            "Select All"
+                                        style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 15px;",
+                                        h2 {
+                                            style: "margin: 0; font-size: 18px; color: #1b2838;",
+                                            "Mods to Install ({enabled_count} selected)"
                                         }
-                                        button {
-                                            style: "background: #666; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
-                                            onclick: deselect_all,
-                                            "Deselect All"
+                                        div {
+                                            button {
+                                                style: "background: #5c7e10; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-right: 5px;",
+                                                onclick: select_all,
+                                                "Select All"
+                                            }
+                                            button {
+                                                style: "background: #666; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                                                onclick: deselect_all,
+                                                "Deselect All"
+                                            }
                                         }
                                     }
-                                }
                                 
-                                div {
-                                    style: "display: flex; flex-direction: column; gap: 10px;",
-                                    for mod_item in mods.read().iter() {
-                                        div {
-                                            key: "{mod_item.id}",
-                                            style: "border: 1px solid #e0e0e0; border-radius: 4px; padding: 15px; background: #fafafa; cursor: pointer; transition: background 0.2s;",
-                                            onclick: {
-                                                let mod_id = mod_item.id.clone();
-                                                move |_| toggle_mod(mod_id.clone())
-                                            },
-                                            
+                                    div {
+                                        style: "display: flex; gap: 8px; align-items: center; margin-bottom: 10px; font-size: 13px; color: #666;",
+                                        input {
+                                            r#type: "search",
+                                            placeholder: "Filter mods...",
+                                            style: "flex: 1; padding: 6px;",
+                                            value: "{mod_filter}",
+                                            oninput: move |e: FormEvent| mod_filter.set(e.value()),
+                                        }
+                                        label {
+                                            input { r#type: "checkbox", checked: show_libraries(), onchange: move |e: FormEvent| show_libraries.set(e.checked()) }
+                                            " Show libraries (advanced)"
+                                        }
+                                    }
+
+                                    div {
+                                        style: "display: flex; flex-direction: column; gap: 10px;",
+                                        if sections.is_empty() {
+                                            p { style: "font-size: 13px; color: #999;", "No mods match the filter." }
+                                        }
+                                        for (section, section_mods) in sections.iter() {
                                             div {
-                                                style: "display: flex; align-items: start; gap: 15px;",
-                                                input {
-                                                    r#type: "checkbox",
-                                                    checked: mod_item.enabled,
-                                                    style: "margin-top: 2px; cursor: pointer; width: 18px; height: 18px; flex-shrink: 0;",
-                                                }
-                                                img {
-                                                    src: "{mod_item.icon_url}",
-                                                    style: "width: 64px; height: 64px; border-radius: 4px; object-fit: cover; flex-shrink: 0;",
-                                                    alt: "{mod_item.name}"
-                                                }
+                                                key: "{section}",
                                                 div {
-                                                    style: "flex: 1;",
-                                                    h3 {
-                                                        style: "margin: 0 0 5px 0; font-size: 16px; color: #1b2838;",
-                                                        "{mod_item.name} "
-                                                        span {
-                                                            style: "font-size: 13px; color: #999; font-weight: normal;",
-                                                            "v{mod_item.version}"
+                                                    style: "display: flex; justify-content: space-between; align-items: center; padding: 6px 0; border-bottom: 1px solid #ddd; cursor: pointer; font-size: 14px; font-weight: bold; color: #1b2838;",
+                                                    onclick: {
+                                                        let section = section.clone();
+                                                        move |_| {
+                                                            let mut collapsed = collapsed_sections.write();
+                                                            match collapsed.iter().position(|name| *name == section) {
+                                                                Some(index) => { collapsed.remove(index); }
+                                                                None => collapsed.push(section.clone()),
+                                                            }
                                                         }
+                                                    },
+                                                    span { if collapsed_sections.read().contains(section) { "▶ {section}" } else { "▼ {section}" } }
+                                                    span {
+                                                        style: "font-size: 12px; color: #999; font-weight: normal;",
+                                                        "{section_mods.iter().filter(|m| m.enabled).count()}/{section_mods.len()} selected"
                                                     }
-                                                    p {
-                                                        style: "margin: 0; color: #666; font-size: 13px; line-height: 1.4;",
-                                                        "{mod_item.description}"
+                                                }
+                                                if !collapsed_sections.read().contains(section) {
+                                                    div {
+                                                        style: "display: flex; flex-direction: column; gap: 10px; margin-top: 10px;",
+                                                        for mod_item in section_mods.iter() {
+                                                            div {
+                                                                key: "{mod_item.id}",
+                                                                style: "border: 1px solid #e0e0e0; border-radius: 4px; padding: 15px; background: #fafafa; cursor: pointer; transition: background 0.2s;",
+                                                                onclick: {
+                                                                    let mod_id = mod_item.id.clone();
+                                                                    move |_| toggle_mod(mod_id.clone())
+                                                                },
+                                            
+                                                                div {
+                                                                    style: "display: flex; align-items: start; gap: 15px;",
+                                                                    input {
+                                                                        r#type: "checkbox",
+                                                                        checked: mod_item.enabled,
+                                                                        disabled: selection::is_locked(mod_item),
+                                                                        style: "margin-top: 2px; cursor: pointer; width: 18px; height: 18px; flex-shrink: 0;",
+                                                                    }
+                                                                    img {
+                                                                        src: "{mod_item.icon_url}",
+                                                                        style: "width: 64px; height: 64px; border-radius: 4px; object-fit: cover; flex-shrink: 0;",
+                                                                        alt: "{mod_item.name}"
+                                                                    }
+                                                                    div {
+                                                                        style: "flex: 1;",
+                                                                        h3 {
+                                                                            style: "margin: 0 0 5px 0; font-size: 16px; color: #1b2838;",
+                                                                            "{mod_item.name} "
+                                                                            span {
+                                                                                style: "font-size: 13px; color: #999; font-weight: normal;",
+                                                                                "v{mod_item.version}"
+                                                                            }
+                                                                            if selection::is_locked(mod_item) {
+                                                                                span { style: "font-size: 11px; color: white; background: #1b2838; border-radius: 3px; padding: 1px 5px; margin-left: 6px; font-weight: normal;", "Required" }
+                                                                            } else if mod_item.requirement == bepmod::Requirement::Optional {
+                                                                                span { style: "font-size: 11px; color: #666; background: #e0e0e0; border-radius: 3px; padding: 1px 5px; margin-left: 6px; font-weight: normal;", "Optional" }
+                                                                            }
+                                                                        }
+                                                                        p {
+                                                                            style: "margin: 0; color: #666; font-size: 13px; line-height: 1.4;",
+                                                                            "{mod_item.description}"
+                                                                        }
+                                                                    }
+                                                                    button {
+                                                                        style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; flex-shrink: 0;",
+                                                                        onclick: {
+                                                                            let mod_item = mod_item.clone();
+                                                                            move |e: MouseEvent| {
+                                                                                e.stop_propagation();
+                                                                                details_for.set(Some(mod_item.clone()));
+                                                                            }
+                                                                        },
+                                                                        "Details"
+                                                                    }
+                                                                }
+                                                            }
+                                                        }
                                                     }
                                                 }
                                             }
@@ -444,7 +766,7 @@ fn app() -> Element {
                                         button {
                                             style: "{primary_style}",
                                             // style: "background: #5c7e10; color: white; padding: 12px 24px; border: none; border-radius: 3px; cursor: pointer; font-size: 16px; font-weight: bold; width: 100%;",
-                                            disabled: install_is_processing() || uninstall_is_processing() || enabled_count == 0,
+                                            disabled: install_is_processing() || uninstall_is_processing() || change_count == 0,
                                             onmousedown: move |_| primary_pressed.set(true),
                                             onmouseup: move |_| primary_pressed.set(false),
                                             onmouseleave: move |_| primary_pressed.set(false),
@@ -453,8 +775,10 @@ fn app() -> Element {
                                             if install_is_processing() {
                                                 Spinner {}
                                                 " Processing..."
+                                            } else if change_count == 0 {
+                                                "Up to Date"
                                             } else {
-                                                "Install Selected ({enabled_count})"
+                                                "Apply Changes ({change_count})"
                                             }
                                         }
 
@@ -476,21 +800,187 @@ fn app() -> Element {
                                         }
                                     }
                                 }
+
+                                label {
+                                    style: "display: flex; align-items: center; gap: 5px; margin-top: 8px; font-size: 12px; color: #666;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: settings().wait_for_game_exit,
+                                        onchange: move |e: FormEvent| {
+                                            let update = settings::Settings { wait_for_game_exit: e.checked(), ..settings() };
+                                            if let Err(e) = update.save() {
+                                                status.set(format!("Error saving settings: {}", e));
+                                            }
+                                            settings.set(update);
+                                        },
+                                    }
+                                    "If Valheim is running, wait for it to close"
+                                }
+
+                                launch::PlayPanel {
+                                    valheim_location,
+                                    server_info,
+                                    status,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                }
+
+                                server::ServerStatusPanel { server_info }
+
+                                modpack::ModpackPanel {
+                                    valheim_location,
+                                    modpack: modpack_info,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                    on_update: sync_to_server,
+                                }
+
+                                compat::CompatPanel {
+                                    valheim_location,
+                                    mods,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                    on_sync: sync_to_server,
+                                }
+
+                                configs::ConfigPresetPanel {
+                                    valheim_location,
+                                    presets: config_presets,
+                                    settings,
+                                    status,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                }
+
+                                conflicts::ConflictPanel { mods }
+
+                                update::PlanPanel { plan, settings, status }
+
+                                collision::CollisionPanel { collisions, settings, status }
+
+                                disable::InstalledPanel {
+                                    valheim_location,
+                                    status,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                }
+
+                                local::LocalInstallPanel {
+                                    valheim_location,
+                                    settings,
+                                    status,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                }
+
+                                r2modman::R2modmanPanel {
+                                    valheim_location,
+                                    mods,
+                                    mods_json_info,
+                                    settings,
+                                    status,
+                                    disabled: install_is_processing() || uninstall_is_processing() || loading_mods(),
+                                }
+
+                                plugins::PluginsPanel {
+                                    valheim_location,
+                                    busy: install_is_processing() || uninstall_is_processing(),
+                                }
+
+                                backup::BackupPanel {
+                                    settings,
+                                    status,
+                                    disabled: install_is_processing() || uninstall_is_processing(),
+                                }
                             }
                         }
                     }
+
+                    logs::LogViewer { valheim_location }
+
+                    activity::ActivityPanel {}
+
+                    support::SupportPanel {
+                        valheim_location,
+                        mods_json_info,
+                        mods,
+                        status,
+                    }
+                }
+            }
+
+            if let Some(mod_item) = details_for() {
+                details::DetailPane {
+                    key: "{mod_item.id}",
+                    mod_item: mod_item.clone(),
+                    on_close: move |_| details_for.set(None),
                 }
             }
         }
     }
 }
 
-async fn get_mods_json() -> Result<Vec<bepmod::BepinexMod>, Box<dyn std::error::Error>> {
-    let response: Vec<bepmod::BepinexMod> = reqwest::get(MODS_JSON_URL).await?.json().await?;
+async fn get_mods_json() -> Result<bepmod::Manifest, LoaderError> {
+    tracing::info!(url = MODS_JSON_URL, "Fetching mod manifest");
+    let body = source::fetch_text(MODS_JSON_URL).await?;
+    let response: bepmod::Manifest = serde_json::from_str(&body)
+        .map_err(|source| LoaderError::ManifestParse { what: "mods.json".to_string(), source })?;
     
     Ok(response)
 }
 
+// Package ids as the sources resolve them (Thunderstore's `full_name`), None unless
+// the whole manifest loaded: orphans must never be judged against part of it
+fn manifest_ids(mods_json: &[bepmod::BepinexMod], complete: bool) -> Option<Vec<String>> {
+    complete.then(|| mods_json.iter()
+        .map(|info| info.package_id())
+        .collect())
+}
+
+fn backup_saves_if_enabled(settings: &settings::Settings, mut status: Signal<String>) -> Result<(), LoaderError> {
+    if !settings.backup_before_changes {
+        return Ok(());
+    }
+
+    status.set("Backing up Valheim saves...".to_string());
+    if let Some(path) = backup::create_backup(settings.backup_retention)? {
+        status.set(format!("Saves backed up to: {}", path.display()));
+    }
+
+    Ok(())
+}
+
+// Starts Valheim once so BepInEx generates its folder layout, then closes it again
+fn first_run_bepinex(valheim_dir: &Path, mut status: Signal<String>) {
+    let valheim_exe = valheim_dir.join("valheim.exe");
+    status.set("Starting Valheim with BepInEx".to_string());
+    tracing::info!(path = %valheim_exe.display(), "Spawning Valheim for the BepInEx first run");
+    let valheim_proc = process::Command::new(valheim_exe).spawn();
+    match valheim_proc {
+        Ok(mut child) => {
+            tracing::debug!(pid = child.id(), "Valheim started");
+            let plugins_dir = valheim_dir.join("BepInEx").join("plugins");
+            let max_time = std::time::Duration::from_secs(300);
+            let start = std::time::Instant::now();
+            while !plugins_dir.is_dir() {
+                if std::time::Instant::now() - start > max_time {
+                    break;
+                }
+                std::thread::sleep(time::Duration::from_secs(1));
+            }
+            
+            match child.kill() {
+                Ok(..) => {
+                    tracing::info!(pid = child.id(), "Closed Valheim");
+                    status.set("Successfully closed Valheim".to_string());
+                }
+                Err(e) => {
+                    tracing::error!(pid = child.id(), error = %e, "Could not close Valheim");
+                    status.set(format!("Error trying to close Valheim: {}", e));
+                }
+            }
+        }
+        Err(e) => { 
+            tracing::error!(error = %e, "Could not start Valheim");
+            status.set(format!("Error starting Valheim with BepInEx: {}", e)); 
+        }
+    }
+}
+
 #[component]
 fn Spinner() -> Element {
     let mut rotation = use_signal(|| 0);
@@ -509,21 +999,49 @@ fn Spinner() -> Element {
     }
 }
 
-async fn download_and_extract_mod(download_url: &str, from_dir: Option<String>, target_dir: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    // Download the zip file
-    let response = reqwest::get(download_url).await?;
-    let bytes = response.bytes().await?;
-    
-    // Save to temporary file
+// Returns the paths of all files written to disk
+async fn download_and_extract_mod(download_url: &str, expected_sha256: Option<&str>, from_dir: Option<String>, target_dir: &PathBuf) -> Result<Vec<PathBuf>, LoaderError> {
     let temp_file = std::env::temp_dir().join("thunderstore_mod.zip");
-    std::fs::write(&temp_file, bytes)?;
+    download_mod(download_url, expected_sha256, &temp_file).await?;
+    
+    let written_files = extract_mod(&temp_file, from_dir, target_dir)?;
+
+    // Clean up temp file
+    std::fs::remove_file(&temp_file).at(&temp_file)?;
+    
+    Ok(written_files)
+}
+
+// Downloads a package archive to `archive_path`, checking its hash when the manifest has one
+async fn download_mod(download_url: &str, expected_sha256: Option<&str>, archive_path: &Path) -> Result<(), LoaderError> {
+    tracing::info!(url = download_url, "Downloading package");
+    let response = source::http_client().get(download_url).send().await.map_err(|e| LoaderError::network(download_url, e))?;
+    if !response.status().is_success() {
+        return Err(LoaderError::HttpStatus { url: download_url.to_string(), status: response.status() });
+    }
+    let bytes = response.bytes().await.map_err(|e| LoaderError::network(download_url, e))?;
+    tracing::debug!(url = download_url, bytes = bytes.len(), "Download finished");
+
+    if let Some(expected) = expected_sha256 {
+        let found = source::sha256_hex(&bytes);
+        if !found.eq_ignore_ascii_case(expected.trim()) {
+            return Err(LoaderError::HashMismatch { url: download_url.to_string(), expected: expected.to_string(), found });
+        }
+    }
     
-    // Extract the zip file
-    let file = std::fs::File::open(&temp_file)?;
-    let mut archive = zip::ZipArchive::new(file)?;
+    std::fs::write(archive_path, bytes).at(archive_path)
+}
+
+// Extracts a package archive (downloaded or local) into `target_dir`, returning the files written
+fn extract_mod(archive_path: &Path, from_dir: Option<String>, target_dir: &Path) -> Result<Vec<PathBuf>, LoaderError> {
+    let file = std::fs::File::open(archive_path).at(archive_path)?;
+    let archive_error = |source| LoaderError::Archive { path: archive_path.to_path_buf(), source };
+    let mut archive = zip::ZipArchive::new(file).map_err(archive_error)?;
+    let mut written_files = Vec::new();
+    tracing::info!(dir = %target_dir.display(), from = ?from_dir, entries = archive.len(), "Extracting package");
     
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
+        let mut file = archive.by_index(i).map_err(archive_error)?;
         let file_path = file.name().to_string();
         match from_dir.clone() {
             Some(internal_dir) => {
@@ -532,13 +1050,14 @@ async fn download_and_extract_mod(download_url: &str, from_dir: Option<String>,
                     let outpath = target_dir.join(relative_path);
                 
                     if file.is_dir() {
-                        std::fs::create_dir_all(&outpath)?;
+                        std::fs::create_dir_all(&outpath).at(&outpath)?;
                     } else {
                         if let Some(p) = outpath.parent() {
-                            std::fs::create_dir_all(p)?;
+                            std::fs::create_dir_all(p).at(p)?;
                         }
-                        let mut outfile = std::fs::File::create(&outpath)?;
-                        std::io::copy(&mut file, &mut outfile)?;
+                        let mut outfile = std::fs::File::create(&outpath).at(&outpath)?;
+                        std::io::copy(&mut file, &mut outfile).at(&outpath)?;
+                        written_files.push(outpath);
                     }
                 }
             },
@@ -546,22 +1065,22 @@ async fn download_and_extract_mod(download_url: &str, from_dir: Option<String>,
                 let outpath = target_dir.join(file.name());
                 
                 if file.is_dir() {
-                    std::fs::create_dir_all(&outpath)?;
+                    std::fs::create_dir_all(&outpath).at(&outpath)?;
                 } else {
                     if let Some(p) = outpath.parent() {
-                        std::fs::create_dir_all(p)?;
+                        std::fs::create_dir_all(p).at(p)?;
                     }
-                    let mut outfile = std::fs::File::create(&outpath)?;
-                    std::io::copy(&mut file, &mut outfile)?;
+                    let mut outfile = std::fs::File::create(&outpath).at(&outpath)?;
+                    std::io::copy(&mut file, &mut outfile).at(&outpath)?;
+                    written_files.push(outpath);
                 }
             }
         }
     }
     
-    // Clean up temp file
-    std::fs::remove_file(&temp_file)?;
-    
-    Ok(())
+    tracing::debug!(files = written_files.len(), "Extraction finished");
+
+    Ok(written_files)
 }
 
 fn find_steam_directory() -> Option<PathBuf> {
@@ -627,7 +1146,7 @@ fn find_steam_directory() -> Option<PathBuf> {
     None
 }
 
-fn uninstall(valheim_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+fn uninstall(valheim_path: Option<PathBuf>) -> Result<(), LoaderError> {
     match valheim_path {
         Some(valheim_path) => {
             let bepinex_dir = valheim_path.join("BepInEx");
@@ -638,15 +1157,16 @@ fn uninstall(valheim_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Er
             let start_game_bepinex = valheim_path.join("start_game_bepinex.sh");
             let start_server_bepinex = valheim_path.join("start_server_bepinex.sh");
             let winhttp_dll = valheim_path.join("winhttp.dll");
+            tracing::info!(path = %valheim_path.display(), "Deleting BepInEx and all mods");
 
-            if bepinex_dir.is_dir() { std::fs::remove_dir_all(bepinex_dir)?; }
-            if doorstop_dir.is_dir() { std::fs::remove_dir_all(doorstop_dir)?; }
-            if changelog.is_file() { std::fs::remove_file(changelog)?; }
-            if doorstop_config.is_file() { std::fs::remove_file(doorstop_config)?; }
-            if doorstop_version.is_file() { std::fs::remove_file(doorstop_version)?; }
-            if start_game_bepinex.is_file() { std::fs::remove_file(start_game_bepinex)?; }
-            if start_server_bepinex.is_file() { std::fs::remove_file(start_server_bepinex)?; }
-            if winhttp_dll.is_file() { std::fs::remove_file(winhttp_dll)?; }
+            if bepinex_dir.is_dir() { std::fs::remove_dir_all(&bepinex_dir).at(&bepinex_dir)?; }
+            if doorstop_dir.is_dir() { std::fs::remove_dir_all(&doorstop_dir).at(&doorstop_dir)?; }
+            if changelog.is_file() { std::fs::remove_file(&changelog).at(&changelog)?; }
+            if doorstop_config.is_file() { std::fs::remove_file(&doorstop_config).at(&doorstop_config)?; }
+            if doorstop_version.is_file() { std::fs::remove_file(&doorstop_version).at(&doorstop_version)?; }
+            if start_game_bepinex.is_file() { std::fs::remove_file(&start_game_bepinex).at(&start_game_bepinex)?; }
+            if start_server_bepinex.is_file() { std::fs::remove_file(&start_server_bepinex).at(&start_server_bepinex)?; }
+            if winhttp_dll.is_file() { std::fs::remove_file(&winhttp_dll).at(&winhttp_dll)?; }
         }
         None => {}
     }
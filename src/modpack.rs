@@ -0,0 +1,91 @@
+use dioxus::prelude::*;
+use std::path::{Path, PathBuf};
+use crate::receipt::{self, ModpackReceipt};
+use crate::source::ModpackInfo;
+
+// Remembers the pack version once every change of its plan has been applied
+pub fn record_applied(valheim_path: &Path, modpack: &ModpackInfo) {
+    let applied = ModpackReceipt { id: modpack.id.clone(), version: modpack.version.clone() };
+    if receipt::load_modpack_receipt(valheim_path).as_ref() == Some(&applied) {
+        return;
+    }
+
+    match receipt::save_modpack_receipt(valheim_path, &applied) {
+        Ok(()) => tracing::info!(modpack = %applied.id, version = %applied.version, "Recorded modpack version"),
+        Err(e) => tracing::warn!(modpack = %applied.id, error = %e, "Could not record modpack version"),
+    }
+}
+
+// `on_update` installs the pack's pinned versions, same as syncing to the server
+#[component]
+pub fn ModpackPanel(valheim_location: Signal<Option<PathBuf>>, modpack: Signal<Option<ModpackInfo>>, disabled: bool, on_update: EventHandler<()>) -> Element {
+    let mut show_changelog = use_signal(|| false);
+    let Some(pack) = modpack() else { return rsx! {}; };
+
+    // A receipt of another pack (the manifest switched packs) counts as nothing installed
+    let installed = valheim_location()
+        .and_then(|path| receipt::load_modpack_receipt(&path))
+        .filter(|applied| applied.id == pack.id)
+        .map(|applied| applied.version);
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            div {
+                style: "display: flex; align-items: center; gap: 10px;",
+                if !pack.icon_url.is_empty() {
+                    img {
+                        src: "{pack.icon_url}",
+                        style: "width: 48px; height: 48px; border-radius: 5px;",
+                    }
+                }
+                div {
+                    style: "flex: 1;",
+                    h2 {
+                        style: "margin: 0; font-size: 16px; color: #1b2838;",
+                        "{pack.name}"
+                    }
+                    p {
+                        style: "margin: 4px 0 0 0; font-size: 12px; color: #666;",
+                        "{pack.description}"
+                    }
+                }
+            }
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center; margin-top: 10px; font-size: 13px;",
+                match installed.as_deref() {
+                    Some(version) if version == pack.version => rsx! {
+                        span { style: "color: #5c7e10; font-weight: bold;", "Modpack {pack.version} installed" }
+                    },
+                    Some(version) => rsx! {
+                        span { style: "color: #b8860b; font-weight: bold;", "Modpack {version} → {pack.version} available" }
+                    },
+                    None => rsx! {
+                        span { style: "color: #999;", "Modpack {pack.version} not installed" }
+                    },
+                }
+                if installed.as_deref() != Some(pack.version.as_str()) {
+                    button {
+                        style: "background: #1b2838; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        disabled: disabled,
+                        onclick: move |_| on_update.call(()),
+                        "Install {pack.version}"
+                    }
+                }
+            }
+            if let Some(changelog) = pack.changelog.as_ref() {
+                button {
+                    style: "background: none; border: none; color: #1b2838; cursor: pointer; font-size: 12px; padding: 0; margin-top: 10px;",
+                    onclick: move |_| show_changelog.set(!show_changelog()),
+                    if show_changelog() { "▼ Changelog" } else { "▶ Changelog" }
+                }
+                if show_changelog() {
+                    pre {
+                        style: "max-height: 200px; overflow-y: auto; background: #f5f5f5; padding: 8px; font-size: 11px; white-space: pre-wrap; margin: 6px 0 0 0;",
+                        "{changelog}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use dioxus::prelude::*;
+use std::{cmp::Ordering, path::{Path, PathBuf}};
+use crate::dotnet::{self, AttributeArg};
+use crate::error::LoaderError;
+use crate::{backup, receipt};
+
+// BepInDependency.DependencyFlags.SoftDependency
+const SOFT_DEPENDENCY: i32 = 2;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PluginDependency {
+    pub guid: String,
+    pub min_version: Option<String>,
+    pub hard: bool,
+}
+
+// One [BepInPlugin] class found in a DLL under BepInEx/plugins
+#[derive(Clone, PartialEq, Debug)]
+pub struct PluginInfo {
+    pub guid: String,
+    pub name: String,
+    pub version: String,
+    pub dependencies: Vec<PluginDependency>,
+    pub incompatibilities: Vec<String>,
+    pub path: PathBuf,
+    pub package: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PluginIssue {
+    DuplicateGuid { guid: String, paths: Vec<PathBuf> },
+    MissingDependency { plugin: String, guid: String },
+    OutdatedDependency { plugin: String, guid: String, required: String, found: String },
+    // The plugin declares [BepInIncompatibility] on another installed plugin
+    Incompatible { plugin: String, other: String },
+}
+
+impl std::fmt::Display for PluginIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginIssue::DuplicateGuid { guid, paths } => {
+                let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "{} is installed more than once: {}", guid, paths.join(", "))
+            }
+            PluginIssue::MissingDependency { plugin, guid } => write!(f, "{} requires {}, which is not installed", plugin, guid),
+            PluginIssue::OutdatedDependency { plugin, guid, required, found } => {
+                write!(f, "{} requires {} v{} or newer, found v{}", plugin, guid, required, found)
+            }
+            PluginIssue::Incompatible { plugin, other } => write!(f, "{} declares itself incompatible with {}, BepInEx won't load it", plugin, other),
+        }
+    }
+}
+
+// Compares dotted numeric versions ("1.2.10" > "1.2.9"), missing parts count as 0
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| -> Vec<u64> {
+        version.split('.').map(|part| part.trim().parse().unwrap_or(0)).collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    for i in 0..a.len().max(b.len()) {
+        match a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+
+    Ordering::Equal
+}
+
+// Collects the BepInEx plugin declarations of a single assembly
+pub fn read_plugins(path: &Path) -> Result<Vec<PluginInfo>, LoaderError> {
+    let assembly = dotnet::read_assembly(path)?;
+    let bepinex_attributes: Vec<&dotnet::TypeAttribute> = assembly.type_attributes.iter()
+        .filter(|attribute| attribute.namespace == "BepInEx")
+        .collect();
+
+    let plugins = bepinex_attributes.iter()
+        .filter(|attribute| attribute.name == "BepInPlugin")
+        .filter_map(|plugin| {
+            let [guid, name, version] = plugin.args.as_slice() else { return None; };
+            let on_type = bepinex_attributes.iter().filter(|attribute| attribute.type_row == plugin.type_row);
+
+            let dependencies = on_type.clone()
+                .filter(|attribute| attribute.name == "BepInDependency")
+                .filter_map(|dependency| match dependency.args.as_slice() {
+                    [guid, AttributeArg::String(min_version)] => Some(PluginDependency {
+                        guid: guid.as_str()?.to_string(),
+                        min_version: min_version.clone(),
+                        hard: true,
+                    }),
+                    [guid, AttributeArg::Int(flags)] => Some(PluginDependency {
+                        guid: guid.as_str()?.to_string(),
+                        min_version: None,
+                        hard: flags & SOFT_DEPENDENCY == 0,
+                    }),
+                    [guid] => Some(PluginDependency { guid: guid.as_str()?.to_string(), min_version: None, hard: true }),
+                    _ => None,
+                })
+                .collect();
+            let incompatibilities = on_type
+                .filter(|attribute| attribute.name == "BepInIncompatibility")
+                .filter_map(|attribute| Some(attribute.args.first()?.as_str()?.to_string()))
+                .collect();
+
+            Some(PluginInfo {
+                guid: guid.as_str()?.to_string(),
+                name: name.as_str()?.to_string(),
+                version: version.as_str()?.to_string(),
+                dependencies,
+                incompatibilities,
+                path: path.to_path_buf(),
+                package: None,
+            })
+        })
+        .collect();
+
+    Ok(plugins)
+}
+
+// Every plugin under BepInEx/plugins, tagged with the package that installed it
+pub fn scan_plugins(valheim_path: &Path) -> Vec<PluginInfo> {
+    let receipts = receipt::load_receipts(valheim_path);
+    let dlls = backup::collect_files(&valheim_path.join("BepInEx").join("plugins")).unwrap_or_default();
+
+    let mut plugins: Vec<PluginInfo> = dlls.iter()
+        .filter(|file| file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("dll")))
+        .filter_map(|file| read_plugins(file).ok())
+        .flatten()
+        .map(|mut plugin| {
+            plugin.package = receipts.iter()
+                .find(|r| r.files.iter().any(|file| valheim_path.join(file) == plugin.path))
+                .map(|r| r.name.clone());
+            plugin
+        })
+        .collect();
+
+    plugins.sort_by_key(|plugin| plugin.name.to_lowercase());
+    plugins
+}
+
+pub fn find_issues(plugins: &[PluginInfo]) -> Vec<PluginIssue> {
+    let mut issues = Vec::new();
+
+    let mut seen: Vec<&str> = Vec::new();
+    for plugin in plugins {
+        if seen.contains(&plugin.guid.as_str()) {
+            continue;
+        }
+        seen.push(&plugin.guid);
+
+        let paths: Vec<PathBuf> = plugins.iter()
+            .filter(|other| other.guid == plugin.guid)
+            .map(|other| other.path.clone())
+            .collect();
+        if paths.len() > 1 {
+            issues.push(PluginIssue::DuplicateGuid { guid: plugin.guid.clone(), paths });
+        }
+    }
+
+    for plugin in plugins {
+        for dependency in plugin.dependencies.iter().filter(|dependency| dependency.hard) {
+            match plugins.iter().find(|other| other.guid == dependency.guid) {
+                None => issues.push(PluginIssue::MissingDependency {
+                    plugin: plugin.name.clone(),
+                    guid: dependency.guid.clone(),
+                }),
+                Some(found) => {
+                    if let Some(required) = &dependency.min_version
+                        && compare_versions(&found.version, required) == Ordering::Less {
+                        issues.push(PluginIssue::OutdatedDependency {
+                            plugin: plugin.name.clone(),
+                            guid: dependency.guid.clone(),
+                            required: required.clone(),
+                            found: found.version.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    for plugin in plugins {
+        for guid in &plugin.incompatibilities {
+            if let Some(other) = plugins.iter().find(|other| other.guid == *guid) {
+                issues.push(PluginIssue::Incompatible { plugin: plugin.name.clone(), other: other.name.clone() });
+            }
+        }
+    }
+
+    issues
+}
+
+// `busy` is only taken so the panel rescans once an install/uninstall finishes.
+// Reading every DLL is slow, so the scan runs off the UI thread and only when either changes.
+#[component]
+pub fn PluginsPanel(valheim_location: Signal<Option<PathBuf>>, busy: bool) -> Element {
+    let mut expanded = use_signal(|| false);
+    let scan = use_resource(use_reactive!(|busy| async move {
+        let Some(valheim_path) = valheim_location() else { return Vec::new(); };
+        if busy {
+            return Vec::new();
+        }
+        tokio::task::spawn_blocking(move || scan_plugins(&valheim_path)).await.unwrap_or_default()
+    }));
+
+    if valheim_location.read().is_none() {
+        return rsx! {};
+    }
+    let plugins = scan.read().clone().unwrap_or_default();
+    let issues = find_issues(&plugins);
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center; cursor: pointer;",
+                onclick: move |_| expanded.set(!expanded()),
+                h2 {
+                    style: "margin: 0; font-size: 16px; color: #1b2838;",
+                    "Installed Plugins ({plugins.len()})"
+                }
+                span { style: "color: #999; font-size: 12px;", if expanded() { "▲" } else { "▼" } }
+            }
+            for issue in issues.iter() {
+                p {
+                    style: "margin: 8px 0 0 0; color: #800000; font-size: 13px;",
+                    "⚠ {issue}"
+                }
+            }
+            if expanded() {
+                for plugin in plugins.iter() {
+                    div {
+                        key: "{plugin.path.display()}-{plugin.guid}",
+                        style: "font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee; margin-top: 4px;",
+                        div {
+                            strong { "{plugin.name}" }
+                            " v{plugin.version}"
+                        }
+                        div {
+                            style: "font-size: 11px; color: #999;",
+                            "{plugin.guid}"
+                            if let Some(package) = &plugin.package {
+                                " · from {package}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
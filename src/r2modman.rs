@@ -0,0 +1,456 @@
+use dioxus::prelude::*;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use crate::bepmod::{is_bepinex_pack, BepinexMod};
+use crate::error::{LoaderError, PathContext};
+use crate::settings::{self, Settings};
+use crate::{backup, catalog, game, receipt, selection, source, Mod};
+
+// r2modman / Thunderstore Mod Manager profile exports: a zip with the mod list
+// in export.r2x (YAML) next to the profile's BepInEx config files
+const PROFILE_ENTRY: &str = "export.r2x";
+const CONFIG_PREFIXES: [&str; 2] = ["BepInEx/config/", "config/"];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProfileMod {
+    // Thunderstore "Namespace-Name", same as `Mod::id`
+    pub name: String,
+    pub version: String,
+    pub enabled: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Profile {
+    pub name: String,
+    pub mods: Vec<ProfileMod>,
+    // Paths relative to BepInEx/config with their content
+    pub configs: Vec<(String, Vec<u8>)>,
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value.strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+// export.r2x only ever has this fixed shape, so a line reader is enough:
+//   profileName: Name
+//   mods:
+//     - name: Namespace-Name
+//       version:
+//         major: 1
+//         minor: 2
+//         patch: 3
+//       enabled: true
+pub fn parse_r2x(content: &str) -> Profile {
+    let mut profile = Profile::default();
+    let mut version = [0u32; 3];
+
+    for line in content.lines() {
+        let line = line.trim();
+        let (is_item, line) = match line.strip_prefix("- ") {
+            Some(rest) => (true, rest.trim()),
+            None => (false, line),
+        };
+        let Some((key, value)) = line.split_once(':') else { continue; };
+        let value = unquote(value);
+
+        if is_item {
+            profile.mods.push(ProfileMod { name: String::new(), version: String::new(), enabled: true });
+            version = [0; 3];
+        }
+        if key.trim() == "profileName" {
+            profile.name = value.to_string();
+            continue;
+        }
+        let Some(current) = profile.mods.last_mut() else { continue; };
+        match key.trim() {
+            "name" => current.name = value.to_string(),
+            "enabled" => current.enabled = value != "false",
+            "major" | "minor" | "patch" => {
+                let index = ["major", "minor", "patch"].iter().position(|part| *part == key.trim()).unwrap_or(0);
+                version[index] = value.parse().unwrap_or(0);
+                current.version = format!("{}.{}.{}", version[0], version[1], version[2]);
+            }
+            _ => {}
+        }
+    }
+
+    profile.mods.retain(|entry| !entry.name.is_empty());
+    profile
+}
+
+pub fn write_r2x(profile: &Profile) -> String {
+    let mut content = format!("profileName: {}\nmods:\n", profile.name);
+    for entry in &profile.mods {
+        let mut parts = entry.version.split('.').map(|part| part.parse::<u32>().unwrap_or(0));
+        let (major, minor, patch) = (parts.next().unwrap_or(0), parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+        content.push_str(&format!(
+            "  - name: {}\n    version:\n      major: {}\n      minor: {}\n      patch: {}\n    enabled: {}\n",
+            entry.name, major, minor, patch, entry.enabled
+        ));
+    }
+    content
+}
+
+pub fn read_r2z(path: &Path) -> Result<Profile, LoaderError> {
+    let archive_error = |source| LoaderError::Archive { path: path.to_path_buf(), source };
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).at(path)?).map_err(archive_error)?;
+
+    let mut content = String::new();
+    archive.by_name(PROFILE_ENTRY).map_err(archive_error)?.read_to_string(&mut content).at(path)?;
+    let mut profile = parse_r2x(&content);
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(archive_error)?;
+        // enclosed_name drops entries that would escape the config folder
+        let Some(name) = file.enclosed_name().map(|name| name.to_string_lossy().replace('\\', "/")) else { continue; };
+        let Some(relative) = CONFIG_PREFIXES.iter().find_map(|prefix| name.strip_prefix(prefix)) else { continue; };
+        if file.is_dir() || relative.is_empty() {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).at(path)?;
+        profile.configs.push((relative.to_string(), bytes));
+    }
+
+    Ok(profile)
+}
+
+// The current selection as a profile. Only Thunderstore packages are listed,
+// r2modman couldn't resolve GitHub, URL or local ones
+pub fn current_profile(valheim_path: &Path, mods: &[Mod]) -> Result<Profile, LoaderError> {
+    let receipts = receipt::load_receipts(valheim_path);
+    let mods = mods.iter()
+        .filter(|mod_item| mod_item.enabled && mod_item.thunderstore)
+        .map(|mod_item| ProfileMod {
+            name: mod_item.id.clone(),
+            version: mod_item.version.clone(),
+            enabled: !receipts.iter().any(|r| r.id == mod_item.id && r.disabled),
+        })
+        .collect();
+
+    let config_dir = valheim_path.join("BepInEx").join("config");
+    let mut configs = Vec::new();
+    for file in backup::collect_files(&config_dir).at(&config_dir)? {
+        let Ok(relative) = file.strip_prefix(&config_dir) else { continue; };
+        configs.push((relative.to_string_lossy().replace('\\', "/"), std::fs::read(&file).at(&file)?));
+    }
+
+    Ok(Profile { name: "CumHeim".to_string(), mods, configs })
+}
+
+pub fn write_r2z(target: &Path, profile: &Profile) -> Result<(), LoaderError> {
+    let archive_error = |source| LoaderError::Archive { path: target.to_path_buf(), source };
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(target).at(target)?);
+    let options = zip::write::SimpleFileOptions::default();
+
+    writer.start_file(PROFILE_ENTRY, options).map_err(archive_error)?;
+    writer.write_all(write_r2x(profile).as_bytes()).at(target)?;
+    for (relative, bytes) in &profile.configs {
+        writer.start_file(format!("BepInEx/config/{}", relative), options).map_err(archive_error)?;
+        writer.write_all(bytes).at(target)?;
+    }
+    writer.finish().map_err(archive_error)?;
+
+    Ok(())
+}
+
+// Config files of the profile that are new or differ from the player's
+pub fn changed_configs(valheim_path: &Path, profile: &Profile) -> Vec<String> {
+    let config_dir = valheim_path.join("BepInEx").join("config");
+    profile.configs.iter()
+        .filter(|(relative, bytes)| std::fs::read(config_dir.join(relative)).ok().as_ref() != Some(bytes))
+        .map(|(relative, _)| relative.clone())
+        .collect()
+}
+
+// Zips BepInEx/config into the loader's data dir before a profile overwrites it
+pub fn backup_configs(valheim_path: &Path) -> Result<PathBuf, LoaderError> {
+    let dir = settings::app_data_dir().join("installs");
+    std::fs::create_dir_all(&dir).at(&dir)?;
+
+    let created = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    let backup_path = dir.join(format!("configs-{}-{:03}.zip", created.as_secs(), created.subsec_millis()));
+    let archive_error = |source| LoaderError::Archive { path: backup_path.clone(), source };
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&backup_path).at(&backup_path)?);
+    let options = zip::write::SimpleFileOptions::default();
+
+    let config_dir = valheim_path.join("BepInEx").join("config");
+    for file in backup::collect_files(&config_dir).at(&config_dir)? {
+        let Ok(relative) = file.strip_prefix(valheim_path) else { continue; };
+        writer.start_file(relative.to_string_lossy().replace('\\', "/"), options).map_err(archive_error)?;
+        writer.write_all(&std::fs::read(&file).at(&file)?).at(&backup_path)?;
+    }
+    writer.finish().map_err(archive_error)?;
+
+    Ok(backup_path)
+}
+
+pub fn import_configs(valheim_path: &Path, profile: &Profile) -> Result<usize, LoaderError> {
+    let config_dir = valheim_path.join("BepInEx").join("config");
+    for (relative, bytes) in &profile.configs {
+        let path = config_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).at(parent)?;
+        }
+        std::fs::write(&path, bytes).at(&path)?;
+    }
+
+    Ok(profile.configs.len())
+}
+
+// Applies a profile's mod list to the selection. Mods the manifest already has just
+// follow the profile's flag, others are fetched at the profile's version and become
+// personal mods, so they stay listed after a restart. BepInEx is the loader's own
+// business and is never taken from a profile. Returns how many mods were added.
+pub async fn import_selection(
+    profile: &Profile,
+    mut mods: Signal<Vec<Mod>>,
+    mut mods_json_info: Signal<Vec<BepinexMod>>,
+    settings: &mut Settings,
+    mut status: Signal<String>,
+) -> usize {
+    let mut added = 0;
+    for entry in &profile.mods {
+        let name = entry.name.split_once('-').map_or(entry.name.as_str(), |(_, name)| name);
+        if is_bepinex_pack(name) {
+            continue;
+        }
+        if let Some(mod_item) = mods.write().iter_mut().find(|m| m.id == entry.name) {
+            mod_item.enabled = entry.enabled || selection::is_locked(mod_item);
+            continue;
+        }
+        if !entry.enabled {
+            continue;
+        }
+
+        let reference = format!("{}-{}", entry.name, entry.version);
+        let Some(manifest_entry) = catalog::personal_entry(&reference) else {
+            tracing::warn!(package = %entry.name, "Skipping profile entry that is not a Thunderstore package");
+            continue;
+        };
+        status.set(format!("Fetching {} v{}...", entry.name, entry.version));
+        match source::resolve(&manifest_entry).await {
+            Ok(mut mod_item) => {
+                // Pinned by the profile, not by the server
+                mod_item.required_by_server = false;
+                settings.personal_mods.push(reference);
+                mods_json_info.write().push(manifest_entry);
+                mods.write().push(mod_item);
+                added += 1;
+            }
+            Err(e) => {
+                tracing::warn!(package = %entry.name, error = %e, "Could not fetch profile mod");
+            }
+        }
+    }
+
+    // The profile may have left out dependencies of what it selects
+    selection::complete(&mut mods.write());
+    added
+}
+
+fn pick_profile() -> Option<PathBuf> {
+    use rfd::FileDialog;
+
+    FileDialog::new()
+        .set_title("Select r2modman Profile")
+        .add_filter("r2modman profile", &["r2z"])
+        .pick_file()
+}
+
+fn save_profile_picker() -> Option<PathBuf> {
+    use rfd::FileDialog;
+
+    FileDialog::new()
+        .set_title("Export r2modman Profile")
+        .add_filter("r2modman profile", &["r2z"])
+        .set_file_name("cumheim.r2z")
+        .save_file()
+}
+
+#[component]
+pub fn R2modmanPanel(
+    valheim_location: Signal<Option<PathBuf>>,
+    mods: Signal<Vec<Mod>>,
+    mods_json_info: Signal<Vec<BepinexMod>>,
+    settings: Signal<Settings>,
+    status: Signal<String>,
+    disabled: bool,
+) -> Element {
+    // Imported configs wait here until the player has seen which files they replace
+    let mut pending_configs = use_signal(|| None::<Profile>);
+
+    let import_profile = move |_| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        let Some(path) = pick_profile() else { return; };
+        spawn(async move {
+            let profile = match read_r2z(&path) {
+                Ok(profile) => profile,
+                Err(e) => {
+                    tracing::error!(path = %path.display(), error = %e, "Could not read r2modman profile");
+                    status.set(format!("Error reading profile: {}", e.user_message()));
+                    return;
+                }
+            };
+            tracing::info!(profile = %profile.name, mods = profile.mods.len(), configs = profile.configs.len(), "Importing r2modman profile");
+
+            let mut updated = settings();
+            let added = import_selection(&profile, mods, mods_json_info, &mut updated, status).await;
+            if let Err(e) = updated.save() {
+                status.set(format!("Error saving settings: {}", e));
+            }
+            settings.set(updated);
+
+            let config_count = changed_configs(&valheim_path, &profile).len();
+            status.set(format!(
+                "Imported profile {}: {} mod(s), {} added, {} changed config file(s). Review the plan and press Apply.",
+                profile.name, profile.mods.len(), added, config_count
+            ));
+            pending_configs.set((config_count > 0).then_some(profile));
+        });
+    };
+
+    let apply_configs = move |_| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        let Some(profile) = pending_configs() else { return; };
+        spawn(async move {
+            if let Err(e) = game::ensure_game_closed(settings().wait_for_game_exit, status).await {
+                status.set(format!("Config import cancelled: {}", e.user_message()));
+                return;
+            }
+            let backup_path = match backup_configs(&valheim_path) {
+                Ok(path) => path,
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not back up configs, import cancelled");
+                    status.set(format!("Error backing up configs, nothing was changed: {}", e.user_message()));
+                    return;
+                }
+            };
+            match import_configs(&valheim_path, &profile) {
+                Ok(count) => {
+                    tracing::info!(profile = %profile.name, files = count, backup = %backup_path.display(), "Imported profile configs");
+                    status.set(format!("Imported {} config file(s), your previous configs are at: {}", count, backup_path.display()));
+                    pending_configs.set(None);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not import profile configs");
+                    status.set(format!("Error importing configs: {}", e.user_message()));
+                }
+            }
+        });
+    };
+
+    let export_profile = move |_| {
+        let Some(valheim_path) = valheim_location() else { return; };
+        let Some(target) = save_profile_picker() else { return; };
+        let result = current_profile(&valheim_path, &mods.read())
+            .and_then(|profile| write_r2z(&target, &profile).map(|_| profile.mods.len()));
+        match result {
+            Ok(count) => {
+                tracing::info!(path = %target.display(), mods = count, "Exported r2modman profile");
+                status.set(format!("Exported {} mod(s) to: {}", count, target.display()));
+            }
+            Err(e) => {
+                tracing::error!(path = %target.display(), error = %e, "Could not export r2modman profile");
+                status.set(format!("Error exporting profile: {}", e.user_message()));
+            }
+        }
+    };
+
+    let changed = match (valheim_location(), pending_configs.read().as_ref()) {
+        (Some(valheim_path), Some(profile)) => changed_configs(&valheim_path, profile),
+        _ => Vec::new(),
+    };
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "r2modman Profiles"
+            }
+            p {
+                style: "margin: 0 0 10px 0; font-size: 12px; color: #999;",
+                "Move your mod selection and configs to or from r2modman / Thunderstore Mod Manager (.r2z)"
+            }
+            if !changed.is_empty() {
+                div {
+                    style: "font-size: 13px; color: #666; margin-bottom: 10px;",
+                    p { style: "margin: 0 0 5px 0;", "The profile replaces these config files, yours are backed up first:" }
+                    for file in changed.iter() {
+                        div { key: "{file}", style: "font-size: 12px; font-family: monospace;", "{file}" }
+                    }
+                    button {
+                        style: "background: #1b2838; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin: 5px 5px 0 0;",
+                        disabled: disabled,
+                        onclick: apply_configs,
+                        "Apply Configs"
+                    }
+                    button {
+                        style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-top: 5px;",
+                        onclick: move |_| pending_configs.set(None),
+                        "Discard"
+                    }
+                }
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-right: 5px;",
+                disabled: disabled,
+                onclick: import_profile,
+                "Import .r2z..."
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                disabled: disabled,
+                onclick: export_profile,
+                "Export .r2z..."
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> Profile {
+        Profile {
+            name: "Friday Night".to_string(),
+            mods: vec![
+                ProfileMod { name: "denikson-BepInExPack_Valheim".to_string(), version: "5.4.2202".to_string(), enabled: true },
+                ProfileMod { name: "ValheimModding-Jotunn".to_string(), version: "2.20.1".to_string(), enabled: true },
+                ProfileMod { name: "RandyKnapp-EquipmentAndQuickSlots".to_string(), version: "2.1.9".to_string(), enabled: false },
+            ],
+            configs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn r2x_round_trips() {
+        let profile = profile();
+        assert_eq!(parse_r2x(&write_r2x(&profile)), profile);
+    }
+
+    #[test]
+    fn reads_r2modman_exports() {
+        let exported = "profileName: 'Friday Night'\r\nmods:\r\n  - name: denikson-BepInExPack_Valheim\r\n    version:\r\n      major: 5\r\n      minor: 4\r\n      patch: 2202\r\n    enabled: true\r\n  - name: \"ValheimModding-Jotunn\"\r\n    version:\r\n      major: 2\r\n      minor: 20\r\n      patch: 1\r\n    enabled: true\r\n  - name: RandyKnapp-EquipmentAndQuickSlots\r\n    version:\r\n      major: 2\r\n      minor: 1\r\n      patch: 9\r\n    enabled: false\r\n";
+        assert_eq!(parse_r2x(exported), profile());
+    }
+
+    #[test]
+    fn profile_zip_round_trips() {
+        let path = std::env::temp_dir().join(format!("cumheim-test-{}.r2z", std::process::id()));
+        let profile = Profile {
+            configs: vec![("com.example.mod.cfg".to_string(), b"[General]\nEnabled = true\n".to_vec())],
+            ..profile()
+        };
+
+        write_r2z(&path, &profile).unwrap();
+        let read = read_r2z(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap(), profile);
+    }
+}
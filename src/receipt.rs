@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::Mod;
+use crate::error::{LoaderError, PathContext};
+
+// Loader bookkeeping lives inside BepInEx so a full uninstall removes it as well
+const MODLOADER_DIR: &str = "modloader";
+
+// Records which files an installed package put into the Valheim directory
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct InstallReceipt {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub files: Vec<PathBuf>,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+// Which version of the manifest's modpack was last applied completely
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ModpackReceipt {
+    pub id: String,
+    pub version: String,
+}
+
+pub fn modloader_dir(valheim_path: &Path) -> PathBuf {
+    valheim_path.join("BepInEx").join(MODLOADER_DIR)
+}
+
+fn receipts_dir(valheim_path: &Path) -> PathBuf {
+    modloader_dir(valheim_path).join("receipts")
+}
+
+fn receipt_path(valheim_path: &Path, id: &str) -> PathBuf {
+    receipts_dir(valheim_path).join(format!("{}.json", id))
+}
+
+// Where the files of a disabled package are parked
+pub fn disabled_dir(valheim_path: &Path, id: &str) -> PathBuf {
+    modloader_dir(valheim_path).join("disabled").join(id)
+}
+
+pub fn load_receipts(valheim_path: &Path) -> Vec<InstallReceipt> {
+    let mut receipts: Vec<InstallReceipt> = std::fs::read_dir(receipts_dir(valheim_path))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    receipts.sort_by(|a, b| a.name.cmp(&b.name));
+    receipts
+}
+
+pub fn save_receipt(valheim_path: &Path, receipt: &InstallReceipt) -> Result<(), LoaderError> {
+    let dir = receipts_dir(valheim_path);
+    std::fs::create_dir_all(&dir).at(&dir)?;
+    let path = receipt_path(valheim_path, &receipt.id);
+    let content = serde_json::to_string_pretty(receipt).map_err(std::io::Error::other).at(&path)?;
+    std::fs::write(&path, content).at(&path)?;
+
+    Ok(())
+}
+
+pub fn load_modpack_receipt(valheim_path: &Path) -> Option<ModpackReceipt> {
+    let content = std::fs::read_to_string(modloader_dir(valheim_path).join("modpack.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn save_modpack_receipt(valheim_path: &Path, receipt: &ModpackReceipt) -> Result<(), LoaderError> {
+    let dir = modloader_dir(valheim_path);
+    std::fs::create_dir_all(&dir).at(&dir)?;
+    let path = dir.join("modpack.json");
+    let content = serde_json::to_string_pretty(receipt).map_err(std::io::Error::other).at(&path)?;
+    std::fs::write(&path, content).at(&path)?;
+
+    Ok(())
+}
+
+pub fn delete_receipt(valheim_path: &Path, id: &str) -> Result<(), LoaderError> {
+    let path = receipt_path(valheim_path, id);
+    if path.is_file() {
+        std::fs::remove_file(&path).at(&path)?;
+    }
+
+    Ok(())
+}
+
+// Writes the receipt for a freshly extracted package. Any parked copy from an
+// earlier disable is dropped since the new files supersede it.
+pub fn record_install(valheim_path: &Path, mod_item: &Mod, written_files: &[PathBuf]) -> Result<(), LoaderError> {
+    let stale_disabled = disabled_dir(valheim_path, &mod_item.id);
+    if stale_disabled.is_dir() {
+        std::fs::remove_dir_all(&stale_disabled).at(&stale_disabled)?;
+    }
+
+    let files = written_files.iter()
+        .filter_map(|file| file.strip_prefix(valheim_path).ok())
+        .map(Path::to_path_buf)
+        .collect();
+
+    save_receipt(valheim_path, &InstallReceipt {
+        id: mod_item.id.clone(),
+        name: mod_item.name.clone(),
+        version: mod_item.version.clone(),
+        files,
+        disabled: false,
+    })
+}
@@ -0,0 +1,234 @@
+use crate::{conflicts, Mod};
+use crate::bepmod::{is_bepinex_pack, Requirement};
+
+// "Namespace-Name-1.0.0" → "Namespace-Name", the form of `Mod::id`
+pub fn dependency_id(reference: &str) -> &str {
+    reference.rsplit_once('-').map(|(id, _)| id).unwrap_or(reference)
+}
+
+// Mods the install can't work without; BepInEx is always one of them
+pub fn is_locked(mod_item: &Mod) -> bool {
+    mod_item.requirement == Requirement::Required || is_bepinex_pack(&mod_item.name)
+}
+
+fn depends_on(mod_item: &Mod, id: &str) -> bool {
+    mod_item.dependencies.iter().any(|dependency| dependency_id(dependency) == id)
+}
+
+// Selects a mod and, transitively, the listed mods it depends on.
+// Returns the names of the mods that were selected along with it.
+pub fn select(mods: &mut [Mod], id: &str) -> Vec<String> {
+    let mut pending = vec![id.to_string()];
+    let mut pulled_in = Vec::new();
+
+    while let Some(current) = pending.pop() {
+        let Some(mod_item) = mods.iter_mut().find(|m| m.id == current) else { continue; };
+        if mod_item.enabled && current != id {
+            continue;
+        }
+        mod_item.enabled = true;
+        if current != id {
+            pulled_in.push(mod_item.name.clone());
+        }
+        pending.extend(mod_item.dependencies.iter().map(|dependency| dependency_id(dependency).to_string()));
+    }
+
+    pulled_in
+}
+
+// Why a mod can't be deselected: it's required, or selected mods still need it
+pub fn deselect_blocker(mods: &[Mod], id: &str) -> Option<String> {
+    let mod_item = mods.iter().find(|m| m.id == id)?;
+    if is_locked(mod_item) {
+        return Some(format!("{} is required by the modpack and can't be deselected.", mod_item.name));
+    }
+
+    let dependents: Vec<&str> = mods.iter()
+        .filter(|m| m.enabled && depends_on(m, id))
+        .map(|m| m.name.as_str())
+        .collect();
+    (!dependents.is_empty()).then(|| format!("{} is needed by {}, deselect those first.", mod_item.name, dependents.join(", ")))
+}
+
+// Ok(status message) when the selection changed, Err(reason) when it was refused
+pub fn toggle(mods: &mut [Mod], id: &str) -> Result<Option<String>, String> {
+    let Some(mod_item) = mods.iter().find(|m| m.id == id) else { return Ok(None); };
+    let name = mod_item.name.clone();
+
+    if mod_item.enabled {
+        if let Some(reason) = deselect_blocker(mods, id) {
+            return Err(reason);
+        }
+        if let Some(mod_item) = mods.iter_mut().find(|m| m.id == id) {
+            mod_item.enabled = false;
+        }
+        return Ok(None);
+    }
+
+    // Tried on a copy first: replaced mods make way, then the mod and everything it pulls in
+    // must get along with the rest of the selection, and no selected mod may still need a replaced one
+    let replaced = replaced_by(mods, id);
+    let mut preview = mods.to_vec();
+    preview.iter_mut().filter(|m| replaced.contains(&m.id)).for_each(|m| m.enabled = false);
+    let pulled_in = select(&mut preview, id);
+
+    let added: Vec<&Mod> = preview.iter().zip(mods.iter()).filter(|(after, before)| after.enabled && !before.enabled).map(|(after, _)| after).collect();
+    for candidate in &added {
+        if let Some((other, reason)) = preview.iter()
+            .filter(|m| m.enabled && m.id != candidate.id)
+            .find_map(|m| conflicts::conflict_between(candidate, m).map(|reason| (m.name.clone(), reason)))
+        {
+            return Err(if candidate.id == id {
+                format!("Can't select {}, it conflicts with {}: {}", name, other, reason)
+            } else {
+                format!("Can't select {}, it needs {} which conflicts with {}: {}", name, candidate.name, other, reason)
+            });
+        }
+    }
+
+    let dropped: Vec<&Mod> = preview.iter().zip(mods.iter()).filter(|(after, before)| before.enabled && !after.enabled).map(|(after, _)| after).collect();
+    if let Some(reason) = dropped.iter().find_map(|m| deselect_blocker(&preview, &m.id)) {
+        return Err(format!("Can't select {}, it replaces a mod that is still in use. {}", name, reason));
+    }
+    let replaced_names: Vec<String> = dropped.iter().map(|m| m.name.clone()).collect();
+
+    for (mod_item, after) in mods.iter_mut().zip(preview) {
+        mod_item.enabled = after.enabled;
+    }
+
+    let mut messages = Vec::new();
+    if !pulled_in.is_empty() {
+        messages.push(format!("Selected {} along with its dependencies: {}", name, pulled_in.join(", ")));
+    }
+    if !replaced_names.is_empty() {
+        messages.push(format!("{} replaces {}, deselected it", name, replaced_names.join(", ")));
+    }
+    Ok((!messages.is_empty()).then(|| messages.join(". ")))
+}
+
+// Selected-or-not mods that `id` replaces and that can be deselected
+fn replaced_by(mods: &[Mod], id: &str) -> Vec<String> {
+    let Some(mod_item) = mods.iter().find(|m| m.id == id) else { return Vec::new(); };
+    mods.iter()
+        .filter(|m| !is_locked(m) && mod_item.replaces.iter().any(|replaced| replaced.trim() == m.id))
+        .map(|m| m.id.clone())
+        .collect()
+}
+
+// Makes a selection consistent: required mods on, and everything selected mods depend on
+pub fn complete(mods: &mut [Mod]) {
+    mods.iter_mut().filter(|m| is_locked(m)).for_each(|m| m.enabled = true);
+    let selected: Vec<String> = mods.iter().filter(|m| m.enabled).map(|m| m.id.clone()).collect();
+    for id in &selected {
+        select(mods, id);
+    }
+    // A replaced mod another selected mod still depends on stays selected
+    for id in &selected {
+        for replaced in replaced_by(mods, id) {
+            if deselect_blocker(mods, &replaced).is_none()
+                && let Some(mod_item) = mods.iter_mut().find(|m| m.id == replaced)
+            {
+                mod_item.enabled = false;
+            }
+        }
+    }
+}
+
+// Selects every mod that can be selected: mods an enabled mod replaces stay off and
+// conflicting ones are skipped. Returns why each skipped mod was refused.
+pub fn select_all(mods: &mut [Mod]) -> Vec<String> {
+    let unselected: Vec<String> = mods.iter().filter(|m| !m.enabled).map(|m| m.id.clone()).collect();
+    let mut refused = Vec::new();
+    for id in &unselected {
+        let replaced = mods.iter().any(|m| m.enabled && m.replaces.iter().any(|r| r.trim() == id));
+        if replaced || mods.iter().any(|m| m.id == *id && m.enabled) {
+            continue;
+        }
+        if let Err(reason) = toggle(mods, id) {
+            refused.push(reason);
+        }
+    }
+    refused
+}
+
+// Deselects everything that isn't required, keeping what the remaining mods depend on
+pub fn deselect_all(mods: &mut [Mod]) {
+    mods.iter_mut().for_each(|m| m.enabled = false);
+    complete(mods);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bepmod::ConflictEntry;
+
+    fn listed(id: &str, enabled: bool, dependencies: &[&str], replaces: &[&str]) -> Mod {
+        Mod {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            icon_url: String::new(),
+            download_url: String::new(),
+            version: "1.0.0".to_string(),
+            enabled,
+            from: None,
+            to: None,
+            required_by_server: false,
+            sha256: None,
+            requirement: Requirement::Recommended,
+            dependencies: dependencies.iter().map(|d| format!("{}-1.0.0", d)).collect(),
+            category: None,
+            group: None,
+            conflicts_with: Vec::new(),
+            replaces: replaces.iter().map(|r| r.to_string()).collect(),
+            thunderstore: true,
+        }
+    }
+
+    fn enabled(mods: &[Mod]) -> Vec<&str> {
+        mods.iter().filter(|m| m.enabled).map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn refuses_a_dependency_that_conflicts_with_the_selection() {
+        let mut lib = listed("A-Lib", false, &[], &[]);
+        lib.conflicts_with.push(ConflictEntry::Id("A-Other".to_string()));
+        let mut mods = vec![listed("A-Mod", false, &["A-Lib"], &[]), lib, listed("A-Other", true, &[], &[])];
+
+        let reason = toggle(&mut mods, "A-Mod").unwrap_err();
+        assert!(reason.contains("A-Lib"), "{}", reason);
+        assert_eq!(enabled(&mods), ["A-Other"]);
+    }
+
+    #[test]
+    fn refuses_to_replace_a_mod_another_selected_mod_needs() {
+        let mut mods = vec![
+            listed("A-New", false, &[], &["A-Old"]),
+            listed("A-Old", true, &[], &[]),
+            listed("A-Addon", true, &["A-Old"], &[]),
+        ];
+
+        assert!(toggle(&mut mods, "A-New").is_err());
+        assert_eq!(enabled(&mods), ["A-Old", "A-Addon"]);
+    }
+
+    #[test]
+    fn replaces_an_unused_mod() {
+        let mut mods = vec![listed("A-New", false, &["A-Lib"], &["A-Old"]), listed("A-Old", true, &[], &[]), listed("A-Lib", false, &[], &[])];
+
+        assert!(toggle(&mut mods, "A-New").is_ok());
+        assert_eq!(enabled(&mods), ["A-New", "A-Lib"]);
+    }
+
+    #[test]
+    fn complete_keeps_a_replaced_mod_that_is_still_needed() {
+        let mut mods = vec![
+            listed("A-New", true, &[], &["A-Old"]),
+            listed("A-Old", true, &[], &[]),
+            listed("A-Addon", true, &["A-Old"], &[]),
+        ];
+
+        complete(&mut mods);
+        assert_eq!(enabled(&mods), ["A-New", "A-Old", "A-Addon"]);
+    }
+}
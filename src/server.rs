@@ -0,0 +1,393 @@
+use dioxus::prelude::*;
+use std::{net::SocketAddr, time::{Duration, Instant}};
+use tokio::net::UdpSocket;
+use crate::bepmod::ServerInfo;
+use crate::error::LoaderError;
+
+// Steam A2S query protocol: https://developer.valvesoftware.com/wiki/Server_queries
+const SINGLE_PACKET: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+const A2S_INFO: u8 = 0x54;
+const A2S_PLAYER: u8 = 0x55;
+const S2C_CHALLENGE: u8 = 0x41;
+const S2A_INFO: u8 = 0x49;
+const S2A_PLAYER: u8 = 0x44;
+const INFO_PAYLOAD: &[u8] = b"Source Engine Query\0";
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServerStatus {
+    pub name: String,
+    pub map: String,
+    pub players: u8,
+    pub max_players: u8,
+    pub version: String,
+    pub ping: Duration,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Player {
+    pub name: String,
+    pub score: i32,
+    pub duration: Duration,
+}
+
+// Little-endian reader over a response packet, every read fails softly on truncated data
+struct PacketReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(data: &'a [u8]) -> PacketReader<'a> {
+        PacketReader { data, position: 0 }
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.data.get(self.position..self.position + N)?.try_into().ok()?;
+        self.position += N;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes::<1>().map(|[byte]| byte)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.bytes().map(i32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.bytes().map(f32::from_le_bytes)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let rest = self.data.get(self.position..)?;
+        let end = rest.iter().position(|&byte| byte == 0)?;
+        self.position += end + 1;
+        Some(String::from_utf8_lossy(&rest[..end]).to_string())
+    }
+
+    // Checks the single-packet header and returns the response type
+    fn header(&mut self) -> Option<u8> {
+        if self.bytes::<4>()? != SINGLE_PACKET {
+            return None;
+        }
+        self.u8()
+    }
+}
+
+pub fn info_request(challenge: Option<[u8; 4]>) -> Vec<u8> {
+    let mut request = SINGLE_PACKET.to_vec();
+    request.push(A2S_INFO);
+    request.extend_from_slice(INFO_PAYLOAD);
+    if let Some(challenge) = challenge {
+        request.extend_from_slice(&challenge);
+    }
+    request
+}
+
+pub fn player_request(challenge: Option<[u8; 4]>) -> Vec<u8> {
+    let mut request = SINGLE_PACKET.to_vec();
+    request.push(A2S_PLAYER);
+    request.extend_from_slice(&challenge.unwrap_or(SINGLE_PACKET));
+    request
+}
+
+// Some(challenge) when the server wants the request repeated with it
+pub fn parse_challenge(packet: &[u8]) -> Option<[u8; 4]> {
+    let mut reader = PacketReader::new(packet);
+    if reader.header()? != S2C_CHALLENGE {
+        return None;
+    }
+    reader.bytes()
+}
+
+pub fn parse_info(packet: &[u8], ping: Duration) -> Option<ServerStatus> {
+    let mut reader = PacketReader::new(packet);
+    if reader.header()? != S2A_INFO {
+        return None;
+    }
+
+    let _protocol = reader.u8()?;
+    let name = reader.string()?;
+    let map = reader.string()?;
+    let _folder = reader.string()?;
+    let _game = reader.string()?;
+    let _app_id = reader.bytes::<2>()?;
+    let players = reader.u8()?;
+    let max_players = reader.u8()?;
+    let _bots = reader.u8()?;
+    let _server_type = reader.u8()?;
+    let _environment = reader.u8()?;
+    let _visibility = reader.u8()?;
+    let _vac = reader.u8()?;
+    let version = reader.string()?;
+
+    Some(ServerStatus { name, map, players, max_players, version, ping })
+}
+
+pub fn parse_players(packet: &[u8]) -> Option<Vec<Player>> {
+    let mut reader = PacketReader::new(packet);
+    if reader.header()? != S2A_PLAYER {
+        return None;
+    }
+
+    let count = reader.u8()?;
+    let mut players = Vec::new();
+    for _ in 0..count {
+        let _index = reader.u8()?;
+        let name = reader.string()?;
+        let score = reader.i32()?;
+        // Remote data, NaN or out of range durations just read as zero
+        let duration = Duration::try_from_secs_f32(reader.f32()?).unwrap_or_default();
+        players.push(Player { name, score, duration });
+    }
+
+    Some(players)
+}
+
+fn query_error(address: SocketAddr, kind: std::io::ErrorKind, message: &str) -> LoaderError {
+    LoaderError::ServerQuery { address: address.to_string(), source: std::io::Error::new(kind, message) }
+}
+
+async fn exchange(socket: &UdpSocket, address: SocketAddr, request: &[u8], timeout: Duration) -> Result<Vec<u8>, LoaderError> {
+    let io_error = |source| LoaderError::ServerQuery { address: address.to_string(), source };
+    socket.send_to(request, address).await.map_err(io_error)?;
+
+    let mut buffer = [0u8; 1400];
+    let (length, _) = tokio::time::timeout(timeout, socket.recv_from(&mut buffer))
+        .await
+        .map_err(|_| query_error(address, std::io::ErrorKind::TimedOut, "no response"))?
+        .map_err(io_error)?;
+
+    Ok(buffer[..length].to_vec())
+}
+
+// Sends a request, answering one challenge round if the server asks for it
+async fn challenged_exchange(
+    socket: &UdpSocket,
+    address: SocketAddr,
+    request: impl Fn(Option<[u8; 4]>) -> Vec<u8>,
+    timeout: Duration,
+) -> Result<Vec<u8>, LoaderError> {
+    let response = exchange(socket, address, &request(None), timeout).await?;
+    match parse_challenge(&response) {
+        Some(challenge) => exchange(socket, address, &request(Some(challenge)), timeout).await,
+        None => Ok(response),
+    }
+}
+
+// Queries A2S_INFO and A2S_PLAYER at `address` (the query port)
+pub async fn query_server(address: SocketAddr, timeout: Duration) -> Result<(ServerStatus, Vec<Player>), LoaderError> {
+    let bind_address: SocketAddr = if address.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" }.parse().expect("valid bind address");
+    let socket = UdpSocket::bind(bind_address).await
+        .map_err(|source| LoaderError::ServerQuery { address: address.to_string(), source })?;
+
+    let started = Instant::now();
+    let response = challenged_exchange(&socket, address, info_request, timeout).await?;
+    let status = parse_info(&response, started.elapsed())
+        .ok_or_else(|| query_error(address, std::io::ErrorKind::InvalidData, "malformed A2S_INFO response"))?;
+
+    // Older servers don't answer player queries, the info alone is still useful
+    let players = match challenged_exchange(&socket, address, player_request, timeout).await {
+        Ok(response) => parse_players(&response).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    Ok((status, players))
+}
+
+// Valheim answers queries on the game port + 1 unless the manifest says otherwise
+pub async fn query_address(server: &ServerInfo) -> Result<SocketAddr, LoaderError> {
+    let address = server.address.trim();
+    let resolved = tokio::net::lookup_host(address).await
+        .map_err(|source| LoaderError::ServerQuery { address: address.to_string(), source })?
+        .next()
+        .ok_or_else(|| LoaderError::ServerQuery {
+            address: address.to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "host did not resolve"),
+        })?;
+
+    let query_port = server.query_port.unwrap_or(resolved.port().saturating_add(1));
+    Ok(SocketAddr::new(resolved.ip(), query_port))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    if minutes >= 60 { format!("{}h {}m", minutes / 60, minutes % 60) } else { format!("{}m", minutes) }
+}
+
+async fn check_server(server: &ServerInfo) -> Result<(ServerStatus, Vec<Player>), String> {
+    let queried = match query_address(server).await {
+        Ok(address) => query_server(address, QUERY_TIMEOUT).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = &queried {
+        tracing::debug!(address = %server.address, error = %e, "Server query failed");
+    }
+
+    queried.map_err(|e| e.user_message())
+}
+
+#[component]
+pub fn ServerStatusPanel(server_info: Signal<Option<ServerInfo>>) -> Element {
+    let mut result = use_signal(|| None::<Result<(ServerStatus, Vec<Player>), String>>);
+
+    // The manifest (and with it the server) arrives after mount, so poll quickly until it does
+    use_future(move || async move {
+        loop {
+            match server_info() {
+                Some(server) => {
+                    result.set(Some(check_server(&server).await));
+                    tokio::time::sleep(REFRESH_INTERVAL).await;
+                }
+                None => tokio::time::sleep(Duration::from_secs(1)).await,
+            }
+        }
+    });
+
+    let refresh = move |_| {
+        spawn(async move {
+            let Some(server) = server_info() else { return; };
+            result.set(None);
+            result.set(Some(check_server(&server).await));
+        });
+    };
+
+    let Some(server) = server_info() else { return rsx! {}; };
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            div {
+                style: "display: flex; justify-content: space-between; align-items: center;",
+                h2 {
+                    style: "margin: 0; font-size: 16px; color: #1b2838;",
+                    "Server"
+                }
+                match &*result.read() {
+                    None => rsx! { span { style: "color: #999; font-size: 12px;", "Checking..." } },
+                    Some(Ok((status, _))) => rsx! {
+                        span { style: "color: #5c7e10; font-size: 12px; font-weight: bold;", "● Online ({status.ping.as_millis()} ms)" }
+                    },
+                    Some(Err(_)) => rsx! { span { style: "color: #800000; font-size: 12px; font-weight: bold;", "● Offline" } },
+                }
+            }
+            p {
+                style: "margin: 8px 0 0 0; font-size: 12px; color: #999;",
+                "{server.address}"
+            }
+            match &*result.read() {
+                Some(Ok((status, players))) => rsx! {
+                    div {
+                        style: "font-size: 13px; color: #666; margin-top: 8px;",
+                        div { strong { "{status.name}" } }
+                        div { "World: {status.map} · v{status.version}" }
+                        div { "Players: {status.players}/{status.max_players}" }
+                    }
+                    for (index, player) in players.iter().filter(|player| !player.name.is_empty()).enumerate() {
+                        div {
+                            key: "{index}",
+                            style: "display: flex; justify-content: space-between; align-items: center; font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                            span { "{player.name}" }
+                            span { style: "color: #999; font-size: 12px;", "{format_duration(player.duration)}" }
+                        }
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    p { style: "margin: 8px 0 0 0; font-size: 12px; color: #800000;", "{e}" }
+                },
+                None => rsx! {},
+            }
+            button {
+                style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px; margin-top: 10px;",
+                onclick: refresh,
+                "Refresh"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info_packet() -> Vec<u8> {
+        let mut packet = SINGLE_PACKET.to_vec();
+        packet.extend_from_slice(&[S2A_INFO, 17]);
+        packet.extend_from_slice(b"Test Server\0Midgard\0valheim\0Valheim\0");
+        packet.extend_from_slice(&[0, 0, 3, 10, 0, b'd', b'w', 0, 0]);
+        packet.extend_from_slice(b"0.219.16\0");
+        packet
+    }
+
+    fn players_packet() -> Vec<u8> {
+        let mut packet = SINGLE_PACKET.to_vec();
+        packet.extend_from_slice(&[S2A_PLAYER, 2]);
+        packet.push(0);
+        packet.extend_from_slice(b"Ragnar\0");
+        packet.extend_from_slice(&5i32.to_le_bytes());
+        packet.extend_from_slice(&3720.0f32.to_le_bytes());
+        packet.push(1);
+        packet.extend_from_slice(b"Lagertha\0");
+        packet.extend_from_slice(&0i32.to_le_bytes());
+        packet.extend_from_slice(&f32::NAN.to_le_bytes());
+        packet
+    }
+
+    // Local stand-in that challenges the first info request, then answers with canned packets
+    async fn stand_in() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let challenge = [1, 2, 3, 4];
+
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 1400];
+            loop {
+                let (length, from) = socket.recv_from(&mut buffer).await.unwrap();
+                let request = &buffer[..length];
+                let reply = if request == info_request(None).as_slice() || request == player_request(None).as_slice() {
+                    let mut packet = SINGLE_PACKET.to_vec();
+                    packet.push(S2C_CHALLENGE);
+                    packet.extend_from_slice(&challenge);
+                    packet
+                } else if request == info_request(Some(challenge)).as_slice() {
+                    info_packet()
+                } else if request == player_request(Some(challenge)).as_slice() {
+                    players_packet()
+                } else {
+                    continue;
+                };
+                socket.send_to(&reply, from).await.unwrap();
+            }
+        });
+
+        address
+    }
+
+    #[tokio::test]
+    async fn queries_info_and_players_through_challenges() {
+        let address = stand_in().await;
+        let (status, players) = query_server(address, Duration::from_secs(2)).await.unwrap();
+
+        assert_eq!(status.name, "Test Server");
+        assert_eq!(status.map, "Midgard");
+        assert_eq!((status.players, status.max_players), (3, 10));
+        assert_eq!(status.version, "0.219.16");
+
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].name, "Ragnar");
+        assert_eq!(players[0].score, 5);
+        assert_eq!(players[0].duration, Duration::from_secs(3720));
+        assert_eq!(players[1].duration, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn times_out_without_a_server() {
+        let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let result = query_server(silent.local_addr().unwrap(), Duration::from_millis(200)).await;
+        assert!(matches!(result, Err(LoaderError::ServerQuery { .. })));
+    }
+}
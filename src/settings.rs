@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use crate::collision::CollisionPolicy;
+use crate::error::{LoaderError, PathContext};
+
+const APP_DIR_NAME: &str = "CumHeim";
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub backup_before_changes: bool,
+    pub backup_retention: usize,
+    // Orphaned packages (no longer in the manifest) the user chose to keep
+    pub kept_packages: Vec<String>,
+    // Wait for Valheim to close instead of cancelling when it is running
+    pub wait_for_game_exit: bool,
+    // Thunderstore packages ("Namespace-Name") the user added on top of the manifest
+    pub personal_mods: Vec<String>,
+    pub collision_policy: CollisionPolicy,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            backup_before_changes: true,
+            backup_retention: 5,
+            kept_packages: Vec::new(),
+            wait_for_game_exit: false,
+            personal_mods: Vec::new(),
+            collision_policy: CollisionPolicy::KeepNewest,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Settings {
+        std::fs::read_to_string(app_data_dir().join(SETTINGS_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), LoaderError> {
+        let dir = app_data_dir();
+        std::fs::create_dir_all(&dir).at(&dir)?;
+        let path = dir.join(SETTINGS_FILE);
+        let content = serde_json::to_string_pretty(self).map_err(std::io::Error::other).at(&path)?;
+        std::fs::write(&path, content).at(&path)?;
+
+        Ok(())
+    }
+}
+
+// Per-user directory for the loader's own files (settings, backups, ...)
+pub fn app_data_dir() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(appdata) = std::env::var("APPDATA") {
+            return PathBuf::from(appdata).join(APP_DIR_NAME);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(home) = std::env::var("HOME") {
+            return PathBuf::from(home).join("Library/Application Support").join(APP_DIR_NAME);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(data_home) = std::env::var("XDG_DATA_HOME") && !data_home.is_empty() {
+            return PathBuf::from(data_home).join(APP_DIR_NAME);
+        }
+        if let Ok(home) = std::env::var("HOME") {
+            return PathBuf::from(home).join(".local/share").join(APP_DIR_NAME);
+        }
+    }
+
+    std::env::temp_dir().join(APP_DIR_NAME)
+}
@@ -0,0 +1,439 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use crate::bepmod::{BepinexMod, Requirement};
+use crate::error::LoaderError;
+use crate::{Mod, ThunderstorePackage, ThunderstoreVersion};
+
+const THUNDERSTORE_API_URL: &str = "https://thunderstore.io/api/experimental";
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+// Where a manifest entry is downloaded from, every source resolves to the same `Mod`
+#[derive(Clone, PartialEq, Debug)]
+pub enum PackageSource {
+    Thunderstore { namespace: String, name: String },
+    // `asset` is a file name pattern where `*` matches anything, the first .zip when unset
+    GitHub { owner: String, repo: String, tag: Option<String>, asset: Option<String> },
+    Url { url: String },
+}
+
+// A Thunderstore package whose dependencies make up the whole mod set
+#[derive(Clone, PartialEq, Debug)]
+pub struct ModpackInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub icon_url: String,
+    pub changelog: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ThunderstoreMarkdown {
+    markdown: Option<String>,
+}
+
+// The README or CHANGELOG of a package version, `document` is "readme" or "changelog"
+pub async fn fetch_markdown(namespace: &str, name: &str, version: &str, document: &str) -> Result<Option<String>, LoaderError> {
+    let url = format!("{}/package/{}/{}/{}/{}/", THUNDERSTORE_API_URL, namespace, name, version, document);
+    let markdown: ThunderstoreMarkdown = fetch_json(&url, format!("{} of {}-{}", document, namespace, name)).await?;
+    Ok(markdown.markdown)
+}
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+// GitHub's API rejects requests without a User-Agent
+pub fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(concat!("CumHeim/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default()
+}
+
+// GETs a URL and hands back the body, treating non-2xx responses as errors
+pub async fn fetch_text(url: &str) -> Result<String, LoaderError> {
+    let response = http_client().get(url).send().await.map_err(|e| LoaderError::network(url, e))?;
+    if !response.status().is_success() {
+        return Err(LoaderError::HttpStatus { url: url.to_string(), status: response.status() });
+    }
+
+    response.text().await.map_err(|e| LoaderError::network(url, e))
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str, what: String) -> Result<T, LoaderError> {
+    let body = fetch_text(url).await?;
+    serde_json::from_str(&body).map_err(|source| LoaderError::ManifestParse { what, source })
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+// `*` matches any run of characters, matching is case-insensitive
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+// The package and the wanted release of it; a pinned version is used as-is instead of the latest
+pub async fn fetch_release(namespace: &str, name: &str, version: Option<&str>) -> Result<(ThunderstorePackage, ThunderstoreVersion), LoaderError> {
+    let package_url = format!("{}/package/{}/{}/", THUNDERSTORE_API_URL, namespace, name);
+    tracing::info!(url = %package_url, "Fetching package");
+    let package: ThunderstorePackage = fetch_json(&package_url, format!("package {}-{}", namespace, name)).await?;
+
+    let release = match version {
+        Some(version) if version != package.latest.version_number => {
+            let version_url = format!("{}/package/{}/{}/{}/", THUNDERSTORE_API_URL, namespace, name, version);
+            tracing::info!(url = %version_url, "Fetching package version");
+            fetch_json::<ThunderstoreVersion>(&version_url, format!("package {}-{} v{}", namespace, name, version)).await?
+        }
+        _ => package.latest.clone(),
+    };
+
+    Ok((package, release))
+}
+
+async fn resolve_thunderstore(entry: &BepinexMod, namespace: &str, name: &str) -> Result<Mod, LoaderError> {
+    let (package, release) = fetch_release(namespace, name, entry.pinned_version()).await?;
+
+    Ok(Mod {
+        id: package.full_name.clone(),
+        name: package.name.clone(),
+        description: release.description.clone(),
+        icon_url: release.icon.clone(),
+        download_url: release.download_url.clone(),
+        version: release.version_number.clone(),
+        enabled: entry.requirement != Requirement::Optional,
+        from: entry.from.clone(),
+        to: entry.to.clone(),
+        required_by_server: entry.pinned_version().is_some(),
+        sha256: entry.sha256.clone(),
+        requirement: entry.requirement,
+        category: entry.category.clone(),
+        group: entry.group.clone(),
+        conflicts_with: entry.conflicts_with.clone(),
+        replaces: entry.replaces.clone(),
+        dependencies: release.dependencies.clone(),
+        thunderstore: true,
+    })
+}
+
+// `api_base` is the GitHub REST API root, swappable for a local mock server
+pub async fn resolve_github(
+    api_base: &str,
+    entry: &BepinexMod,
+    owner: &str,
+    repo: &str,
+    tag: Option<&str>,
+    asset: Option<&str>,
+) -> Result<Mod, LoaderError> {
+    let releases = format!("{}/repos/{}/{}/releases", api_base.trim_end_matches('/'), owner, repo);
+    // A pinned version is fetched by its tag, with or without the usual leading 'v',
+    // so newer releases upstream don't replace what the server runs
+    let (release_url, fallback_url) = match (tag, entry.pinned_version()) {
+        (Some(tag), _) => (format!("{}/tags/{}", releases, tag), None),
+        (None, Some(pinned)) => {
+            let pinned = pinned.trim_start_matches('v');
+            (format!("{}/tags/v{}", releases, pinned), Some(format!("{}/tags/{}", releases, pinned)))
+        }
+        (None, None) => (format!("{}/latest", releases), None),
+    };
+    let what = format!("GitHub release of {}/{}", owner, repo);
+    tracing::info!(url = %release_url, "Fetching GitHub release");
+    let mut result = fetch_json::<GitHubRelease>(&release_url, what.clone()).await;
+    if let Some(fallback_url) = fallback_url
+        && matches!(&result, Err(LoaderError::HttpStatus { status, .. }) if *status == reqwest::StatusCode::NOT_FOUND)
+    {
+        tracing::info!(url = %fallback_url, "Fetching GitHub release");
+        result = fetch_json(&fallback_url, what).await;
+    }
+    let release = result?;
+
+    let download = release.assets.iter()
+        .find(|candidate| match asset {
+            Some(pattern) => matches_pattern(pattern, &candidate.name),
+            None => candidate.name.to_lowercase().ends_with(".zip"),
+        })
+        .ok_or_else(|| LoaderError::MissingAsset {
+            release: format!("{}/{} {}", owner, repo, release.tag_name),
+            pattern: asset.unwrap_or("*.zip").to_string(),
+        })?;
+
+    // The server pins the version it runs, a release that says otherwise isn't that version
+    let version = release.tag_name.trim_start_matches('v').to_string();
+    if let Some(pinned) = entry.pinned_version()
+        && pinned.trim_start_matches('v') != version
+    {
+        return Err(LoaderError::VersionMismatch { package: entry.package_id(), expected: pinned.to_string(), found: version });
+    }
+
+    let description = release.body.as_deref()
+        .and_then(|body| body.lines().map(str::trim).find(|line| !line.is_empty()))
+        .or(release.name.as_deref())
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(Mod {
+        id: entry.package_id(),
+        name: entry.name.trim().to_string(),
+        description,
+        icon_url: format!("https://github.com/{}.png", owner),
+        download_url: download.browser_download_url.clone(),
+        version,
+        enabled: entry.requirement != Requirement::Optional,
+        from: entry.from.clone(),
+        to: entry.to.clone(),
+        required_by_server: entry.pinned_version().is_some(),
+        sha256: entry.sha256.clone(),
+        requirement: entry.requirement,
+        category: entry.category.clone(),
+        group: entry.group.clone(),
+        conflicts_with: entry.conflicts_with.clone(),
+        replaces: entry.replaces.clone(),
+        dependencies: Vec::new(),
+        thunderstore: false,
+    })
+}
+
+// Plain downloads carry no metadata, the manifest's version (or the URL itself) identifies them
+fn resolve_url(entry: &BepinexMod, url: &str) -> Mod {
+    let version = entry.pinned_version()
+        .map(str::to_string)
+        .unwrap_or_else(|| sha256_hex(url.as_bytes())[..8].to_string());
+
+    Mod {
+        id: entry.package_id(),
+        name: entry.name.trim().to_string(),
+        description: url.to_string(),
+        icon_url: String::new(),
+        download_url: url.to_string(),
+        version,
+        enabled: entry.requirement != Requirement::Optional,
+        from: entry.from.clone(),
+        to: entry.to.clone(),
+        required_by_server: entry.pinned_version().is_some(),
+        sha256: entry.sha256.clone(),
+        requirement: entry.requirement,
+        category: entry.category.clone(),
+        group: entry.group.clone(),
+        conflicts_with: entry.conflicts_with.clone(),
+        replaces: entry.replaces.clone(),
+        dependencies: Vec::new(),
+        thunderstore: false,
+    }
+}
+
+pub async fn resolve(entry: &BepinexMod) -> Result<Mod, LoaderError> {
+    match entry.source() {
+        PackageSource::Thunderstore { namespace, name } => resolve_thunderstore(entry, &namespace, &name).await,
+        PackageSource::GitHub { owner, repo, tag, asset } => {
+            resolve_github(GITHUB_API_URL, entry, &owner, &repo, tag.as_deref(), asset.as_deref()).await
+        }
+        PackageSource::Url { url } => Ok(resolve_url(entry, &url)),
+    }
+}
+
+// "Namespace-Name" or "Namespace-Name-1.2.0", Thunderstore names never contain dashes
+pub fn parse_reference(reference: &str) -> Option<(String, String, Option<String>)> {
+    let parts: Vec<&str> = reference.trim().split('-').collect();
+    match parts.as_slice() {
+        [namespace, name] => Some((namespace.to_string(), name.to_string(), None)),
+        [namespace, name, version] => Some((namespace.to_string(), name.to_string(), Some(version.to_string()))),
+        _ => None,
+    }
+}
+
+// Turns a modpack dependency into a manifest entry. Without per-mod mappings each package is
+// extracted whole into its own plugins folder (BepInEx finds plugin DLLs recursively),
+// except BepInExPack which goes next to valheim.exe.
+pub fn dependency_entry(reference: &str) -> Option<BepinexMod> {
+    let (namespace, name, version) = parse_reference(reference)?;
+    let (from, to) = if crate::bepmod::is_bepinex_pack(&name) {
+        (Some(name.clone()), None)
+    } else {
+        (None, Some(format!("BepInEx/plugins/{}-{}", namespace, name)))
+    };
+
+    Some(BepinexMod { namespace, name, from, to, version, ..Default::default() })
+}
+
+// Expands a modpack reference into its info and the manifest entries of its dependencies
+pub async fn resolve_modpack(reference: &str) -> Result<(ModpackInfo, Vec<BepinexMod>), LoaderError> {
+    let Some((namespace, name, version)) = parse_reference(reference) else {
+        return Err(LoaderError::InvalidReference { reference: reference.to_string() });
+    };
+    let (package, release) = fetch_release(&namespace, &name, version.as_deref()).await?;
+
+    let changelog = fetch_markdown(&namespace, &name, &release.version_number, "changelog").await.ok().flatten();
+
+    let entries = release.dependencies.iter().filter_map(|dependency| dependency_entry(dependency)).collect();
+    let info = ModpackInfo {
+        id: package.full_name.clone(),
+        name: package.name.clone(),
+        version: release.version_number.clone(),
+        description: release.description.clone(),
+        icon_url: release.icon.clone(),
+        changelog,
+    };
+
+    Ok((info, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const LATEST: &str = r#"{
+        "tag_name": "v1.2.0",
+        "name": "Release 1.2.0",
+        "body": "\n  Fixes the portal bug\nMore notes",
+        "assets": [
+            { "name": "Source.tar.gz", "browser_download_url": "http://localhost/source" },
+            { "name": "CoolMod-1.2.0-debug.zip", "browser_download_url": "http://localhost/debug" },
+            { "name": "CoolMod-1.2.0.zip", "browser_download_url": "http://localhost/release" }
+        ]
+    }"#;
+
+    const OLD: &str = r#"{
+        "tag_name": "1.1.0",
+        "assets": [{ "name": "CoolMod-1.1.0.zip", "browser_download_url": "http://localhost/old" }]
+    }"#;
+
+    // Serves the latest release (also tagged v1.2.0) and the untagged-'v' 1.1.0,
+    // 404s everything else, one request per connection
+    async fn mock_github() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0u8; 4096];
+                let length = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..length]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+                let (status, body) = match path {
+                    "/repos/o/r/releases/latest" | "/repos/o/r/releases/tags/v1.2.0" => ("200 OK", LATEST),
+                    "/repos/o/r/releases/tags/1.1.0" => ("200 OK", OLD),
+                    _ => ("404 Not Found", "{}"),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    fn github_entry(asset: Option<&str>, version: Option<&str>) -> BepinexMod {
+        BepinexMod {
+            name: "CoolMod".to_string(),
+            github: Some("o/r".to_string()),
+            asset: asset.map(str::to_string),
+            version: version.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn patterns_match_case_insensitively() {
+        assert!(matches_pattern("CoolMod-*.zip", "coolmod-1.2.0.zip"));
+        assert!(matches_pattern("*-*.zip", "CoolMod-1.2.0.zip"));
+        assert!(!matches_pattern("CoolMod-*.zip", "CoolMod-1.2.0.tar.gz"));
+        assert!(!matches_pattern("CoolMod.zip", "CoolMod-1.2.0.zip"));
+    }
+
+    #[tokio::test]
+    async fn resolves_the_latest_release_through_the_asset_pattern() {
+        let api = mock_github().await;
+        let entry = github_entry(Some("OtherMod-*.zip"), None);
+        let result = resolve_github(&api, &entry, "o", "r", None, entry.asset.as_deref()).await;
+        assert!(matches!(result, Err(LoaderError::MissingAsset { pattern, .. }) if pattern == "OtherMod-*.zip"));
+
+        let entry = github_entry(Some("coolmod-*.0.zip"), None);
+        let mod_item = resolve_github(&api, &entry, "o", "r", None, entry.asset.as_deref()).await.unwrap();
+        assert_eq!(mod_item.download_url, "http://localhost/release");
+        assert_eq!(mod_item.version, "1.2.0");
+        assert_eq!(mod_item.id, "o-CoolMod");
+        assert_eq!(mod_item.description, "Fixes the portal bug");
+        assert!(!mod_item.required_by_server);
+    }
+
+    #[tokio::test]
+    async fn picks_the_first_zip_without_a_pattern() {
+        let api = mock_github().await;
+        let entry = github_entry(None, None);
+        let mod_item = resolve_github(&api, &entry, "o", "r", None, None).await.unwrap();
+        assert_eq!(mod_item.download_url, "http://localhost/debug");
+    }
+
+    #[tokio::test]
+    async fn checks_the_tag_against_the_pinned_version() {
+        let api = mock_github().await;
+        let pinned = github_entry(Some("CoolMod-*.0.zip"), Some("v1.2.0"));
+        let mod_item = resolve_github(&api, &pinned, "o", "r", Some("v1.2.0"), pinned.asset.as_deref()).await.unwrap();
+        assert!(mod_item.required_by_server);
+        assert_eq!(mod_item.version, "1.2.0");
+
+        let stale = github_entry(Some("CoolMod-*.0.zip"), Some("1.1.0"));
+        let result = resolve_github(&api, &stale, "o", "r", Some("v1.2.0"), stale.asset.as_deref()).await;
+        assert!(matches!(result, Err(LoaderError::VersionMismatch { expected, found, .. }) if expected == "1.1.0" && found == "1.2.0"));
+    }
+
+    #[tokio::test]
+    async fn fetches_the_pinned_release_instead_of_the_latest() {
+        let api = mock_github().await;
+        let prefixed = github_entry(None, Some("1.2.0"));
+        let mod_item = resolve_github(&api, &prefixed, "o", "r", None, None).await.unwrap();
+        assert_eq!(mod_item.download_url, "http://localhost/debug");
+
+        // Not tagged v1.1.0, found under the bare version
+        let older = github_entry(None, Some("1.1.0"));
+        let mod_item = resolve_github(&api, &older, "o", "r", None, None).await.unwrap();
+        assert_eq!((mod_item.version.as_str(), mod_item.download_url.as_str()), ("1.1.0", "http://localhost/old"));
+
+        let missing = github_entry(None, Some("v0.9.0"));
+        let result = resolve_github(&api, &missing, "o", "r", None, None).await;
+        assert!(matches!(result, Err(LoaderError::HttpStatus { url, .. }) if url.ends_with("/releases/tags/0.9.0")));
+    }
+
+    #[tokio::test]
+    async fn missing_releases_are_http_errors() {
+        let api = mock_github().await;
+        let entry = github_entry(None, None);
+        let result = resolve_github(&api, &entry, "o", "r", Some("v9.9.9"), None).await;
+        assert!(matches!(result, Err(LoaderError::HttpStatus { status, .. }) if status == reqwest::StatusCode::NOT_FOUND));
+    }
+}
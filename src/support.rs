@@ -0,0 +1,195 @@
+use dioxus::prelude::*;
+use sha2::{Digest, Sha256};
+use std::{io::Write, path::{Path, PathBuf}};
+use crate::{activity, backup, bepmod, disable, logs, receipt, Mod};
+use crate::error::{LoaderError, PathContext};
+
+// Config keys whose values are blanked out when redaction is on
+const SECRET_KEY_HINTS: [&str; 6] = ["password", "token", "secret", "apikey", "api key", "webhook"];
+
+// Reads the Steam build id of the installed Valheim from its appmanifest
+pub fn valheim_build_id(valheim_path: &Path) -> Option<String> {
+    // <library>/steamapps/common/Valheim -> <library>/steamapps
+    let steamapps = valheim_path.parent()?.parent()?;
+    let manifest = std::fs::read_to_string(steamapps.join("appmanifest_892970.acf")).ok()?;
+
+    manifest.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("\"buildid\""))
+        .and_then(|line| line.split('"').nth(3))
+        .map(str::to_string)
+}
+
+fn sha256_file(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(bytes)))
+}
+
+// Blanks values of `key = value` lines whose key looks like a credential
+pub fn redact_config(content: &str) -> String {
+    content.lines()
+        .map(|line| match line.split_once('=') {
+            Some((key, _)) if !key.trim_start().starts_with('#')
+                && SECRET_KEY_HINTS.iter().any(|hint| key.to_lowercase().contains(hint)) => {
+                format!("{}= <redacted>", key)
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn system_report(valheim_path: Option<&Path>) -> String {
+    let mut report = vec![
+        format!("Loader version: {}", env!("CARGO_PKG_VERSION")),
+        format!("OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+        format!("Steam: {}", crate::find_steam_directory().map(|p| p.display().to_string()).unwrap_or_else(|| "not found".to_string())),
+    ];
+
+    match valheim_path {
+        Some(valheim_path) => {
+            report.push(format!("Valheim: {}", valheim_path.display()));
+            report.push(format!("Valheim build id: {}", valheim_build_id(valheim_path).unwrap_or_else(|| "unknown".to_string())));
+            report.push(format!("BepInEx enabled: {}", match disable::bepinex_enabled(valheim_path) {
+                Some(enabled) => enabled.to_string(),
+                None => "not installed".to_string(),
+            }));
+        }
+        None => report.push("Valheim: not found".to_string()),
+    }
+
+    for location in backup::local_valheim_dirs() {
+        report.push(format!("Valheim data ({}): {}", location.label, location.path.display()));
+    }
+
+    report.join("\n")
+}
+
+// Installed packages with a SHA-256 per file, so modified or missing files stand out
+fn installed_report(valheim_path: &Path) -> String {
+    let mut report = Vec::new();
+    for installed in receipt::load_receipts(valheim_path) {
+        report.push(format!("{} v{} ({}){}", installed.name, installed.version, installed.id, if installed.disabled { " [disabled]" } else { "" }));
+        let root = if installed.disabled { receipt::disabled_dir(valheim_path, &installed.id) } else { valheim_path.to_path_buf() };
+        for file in &installed.files {
+            let hash = sha256_file(&root.join(file)).unwrap_or_else(|| "missing".to_string());
+            report.push(format!("    {}  {}", hash, file.display()));
+        }
+    }
+
+    report.join("\n")
+}
+
+pub fn create_support_bundle(
+    target: &Path,
+    valheim_path: Option<&Path>,
+    mods_json: &[bepmod::BepinexMod],
+    mods: &[Mod],
+    redact: bool,
+) -> Result<(), LoaderError> {
+    let archive_error = |source| LoaderError::Archive { path: target.to_path_buf(), source };
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(target).at(target)?);
+    let options = zip::write::SimpleFileOptions::default();
+
+    writer.start_file("system.txt", options).map_err(archive_error)?;
+    writer.write_all(system_report(valheim_path).as_bytes()).at(target)?;
+
+    writer.start_file("mods.json", options).map_err(archive_error)?;
+    let mods_json = serde_json::to_string_pretty(mods_json).map_err(std::io::Error::other).at(target)?;
+    writer.write_all(mods_json.as_bytes()).at(target)?;
+
+    let resolved: Vec<serde_json::Value> = mods.iter()
+        .map(|m| serde_json::json!({
+            "id": m.id,
+            "version": m.version,
+            "download_url": m.download_url,
+            "selected": m.enabled,
+            "required_by_server": m.required_by_server,
+        }))
+        .collect();
+    writer.start_file("resolved.json", options).map_err(archive_error)?;
+    let resolved = serde_json::to_string_pretty(&resolved).map_err(std::io::Error::other).at(target)?;
+    writer.write_all(resolved.as_bytes()).at(target)?;
+
+    // The loader's own log files, if any have been written
+    let log_dir = activity::log_dir();
+    for file in backup::collect_files(&log_dir).at(&log_dir)? {
+        writer.start_file(format!("loader/{}", file.file_name().unwrap_or_default().to_string_lossy()), options).map_err(archive_error)?;
+        writer.write_all(&std::fs::read(&file).at(&file)?).at(target)?;
+    }
+
+    if let Some(valheim_path) = valheim_path {
+        writer.start_file("installed.txt", options).map_err(archive_error)?;
+        writer.write_all(installed_report(valheim_path).as_bytes()).at(target)?;
+
+        let config_dir = valheim_path.join("BepInEx").join("config");
+        for file in backup::collect_files(&config_dir).at(&config_dir)? {
+            let Ok(relative) = file.strip_prefix(&config_dir) else { continue; };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let content = String::from_utf8_lossy(&std::fs::read(&file).at(&file)?).to_string();
+            writer.start_file(format!("config/{}", relative), options).map_err(archive_error)?;
+            writer.write_all(if redact { redact_config(&content) } else { content }.as_bytes()).at(target)?;
+        }
+
+        for (log_file, name) in [(logs::LogFile::BepInEx, "LogOutput.log"), (logs::LogFile::Unity, "Player.log")] {
+            if let Some(content) = log_file.path(valheim_path).and_then(|path| logs::read_tail(&path)) {
+                writer.start_file(format!("logs/{}", name), options).map_err(archive_error)?;
+                writer.write_all(content.as_bytes()).at(target)?;
+            }
+        }
+    }
+
+    writer.finish().map_err(archive_error)?;
+
+    Ok(())
+}
+
+fn save_file_picker() -> Option<PathBuf> {
+    use rfd::FileDialog;
+
+    FileDialog::new()
+        .set_title("Save Support Bundle")
+        .set_file_name("cumheim-support.zip")
+        .save_file()
+}
+
+#[component]
+pub fn SupportPanel(
+    valheim_location: Signal<Option<PathBuf>>,
+    mods_json_info: Signal<Vec<bepmod::BepinexMod>>,
+    mods: Signal<Vec<Mod>>,
+    status: Signal<String>,
+) -> Element {
+    let mut redact = use_signal(|| true);
+
+    let create_bundle = move |_| {
+        spawn(async move {
+            let Some(target) = save_file_picker() else { return; };
+            status.set("Creating support bundle...".to_string());
+            match create_support_bundle(&target, valheim_location().as_deref(), &mods_json_info.read(), &mods.read(), redact()) {
+                Ok(_) => { status.set(format!("Support bundle saved to: {}", target.display())); }
+                Err(e) => { status.set(format!("Error creating support bundle: {}", e.user_message())); }
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            style: "display: flex; align-items: center; gap: 10px; margin: 20px 0; font-size: 13px; color: #666;",
+            button {
+                style: "background: #1b2838; color: white; padding: 6px 12px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                onclick: create_bundle,
+                "Create Support Bundle"
+            }
+            label {
+                style: "display: flex; align-items: center; gap: 5px;",
+                input {
+                    r#type: "checkbox",
+                    checked: redact(),
+                    onchange: move |e: FormEvent| redact.set(e.checked()),
+                }
+                "Redact passwords and tokens in configs"
+            }
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use dioxus::prelude::*;
+use serde::Deserialize;
+use std::path::Path;
+use crate::Mod;
+use crate::bepmod::is_bepinex_pack;
+use crate::error::{LoaderError, PathContext};
+use crate::receipt::{self, InstallReceipt};
+use crate::settings::Settings;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PlanAction {
+    Install,
+    Update { from: String },
+    Unchanged,
+    Remove,
+    // Installed by the loader but dropped from the manifest since
+    Orphaned { keep: bool },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlanEntry {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub action: PlanAction,
+}
+
+impl PlanEntry {
+    pub fn is_change(&self) -> bool {
+        !matches!(self.action, PlanAction::Unchanged | PlanAction::Orphaned { keep: true })
+    }
+
+    pub fn is_removal(&self) -> bool {
+        matches!(self.action, PlanAction::Remove | PlanAction::Orphaned { keep: false })
+    }
+}
+
+// The Thunderstore manifest.json shipped at the root of every package
+#[derive(Deserialize)]
+struct PackageManifest {
+    name: String,
+    version_number: String,
+}
+
+// Version currently on disk, from the install receipt or, for installs that
+// predate receipts, from a package manifest.json left in the target folder
+pub fn installed_version(valheim_path: &Path, mod_item: &Mod, receipts: &[InstallReceipt]) -> Option<String> {
+    if let Some(receipt) = receipts.iter().find(|r| r.id == mod_item.id) {
+        return Some(receipt.version.clone());
+    }
+
+    let manifest_path = valheim_path.join(mod_item.to.clone().unwrap_or_default()).join("manifest.json");
+    let content = std::fs::read_to_string(manifest_path).ok()?;
+    let manifest: PackageManifest = serde_json::from_str(content.trim_start_matches('\u{feff}')).ok()?;
+
+    (manifest.name == mod_item.name).then_some(manifest.version_number)
+}
+
+// Diffs the manifest selection against what is installed. Installed packages that
+// are in the manifest but no longer selected are removed, except BepInEx itself
+// which only the full uninstall takes out. Packages missing from the manifest
+// (`manifest_ids`) are orphans and get removed unless listed in `kept`; `None`
+// means the manifest only partly loaded, so nothing can be called an orphan.
+pub fn build_plan(valheim_path: &Path, mods: &[Mod], manifest_ids: Option<&[String]>, receipts: &[InstallReceipt], kept: &[String]) -> Vec<PlanEntry> {
+    let mut plan: Vec<PlanEntry> = mods.iter()
+        .filter_map(|mod_item| {
+            let installed = installed_version(valheim_path, mod_item, receipts);
+            let action = match (mod_item.enabled, installed) {
+                (true, None) => PlanAction::Install,
+                (true, Some(version)) if version == mod_item.version => PlanAction::Unchanged,
+                (true, Some(version)) => PlanAction::Update { from: version },
+                (false, Some(_)) if !is_bepinex_pack(&mod_item.name) && receipts.iter().any(|r| r.id == mod_item.id) => PlanAction::Remove,
+                (false, _) => return None,
+            };
+
+            Some(PlanEntry {
+                id: mod_item.id.clone(),
+                name: mod_item.name.clone(),
+                version: mod_item.version.clone(),
+                action,
+            })
+        })
+        .collect();
+
+    // Without a manifest everything would look orphaned; BepInEx under another id
+    // (the Valheim pack next to the generic one) is never an orphan either
+    if let Some(manifest_ids) = manifest_ids.filter(|ids| !ids.is_empty()) {
+        plan.extend(receipts.iter()
+            .filter(|r| !manifest_ids.contains(&r.id) && !is_bepinex_pack(&r.name))
+            .map(|r| PlanEntry {
+                id: r.id.clone(),
+                name: r.name.clone(),
+                version: r.version.clone(),
+                action: PlanAction::Orphaned { keep: kept.contains(&r.id) },
+            }));
+    }
+
+    plan
+}
+
+// Deletes the files of an installed package (wherever they currently are) and its receipt
+pub fn remove_package(valheim_path: &Path, installed: &InstallReceipt) -> Result<(), LoaderError> {
+    let plugins_dir = valheim_path.join("BepInEx").join("plugins");
+    let root = if installed.disabled {
+        receipt::disabled_dir(valheim_path, &installed.id)
+    } else {
+        valheim_path.to_path_buf()
+    };
+
+    tracing::info!(package = %installed.id, version = %installed.version, files = installed.files.len(), "Removing package");
+    for file in &installed.files {
+        let path = root.join(file);
+        if path.is_file() {
+            tracing::debug!(path = %path.display(), "Deleting file");
+            std::fs::remove_file(&path).at(&path)?;
+        }
+
+        // Drop folders the package created under plugins once they are empty
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if !dir.starts_with(&plugins_dir) || dir == plugins_dir || std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+    }
+
+    let parked_dir = receipt::disabled_dir(valheim_path, &installed.id);
+    if parked_dir.is_dir() {
+        std::fs::remove_dir_all(&parked_dir).at(&parked_dir)?;
+    }
+
+    receipt::delete_receipt(valheim_path, &installed.id)
+}
+
+#[component]
+pub fn PlanPanel(plan: Vec<PlanEntry>, settings: Signal<Settings>, status: Signal<String>) -> Element {
+    let count = |wanted: fn(&PlanAction) -> bool| plan.iter().filter(|entry| wanted(&entry.action)).count();
+    let new_count = count(|a| matches!(a, PlanAction::Install));
+    let updated_count = count(|a| matches!(a, PlanAction::Update { .. }));
+    let unchanged_count = count(|a| matches!(a, PlanAction::Unchanged));
+    let removed_count = count(|a| matches!(a, PlanAction::Remove | PlanAction::Orphaned { keep: false }));
+
+    let mut toggle_keep = move |id: String| {
+        let mut update = settings();
+        if update.kept_packages.contains(&id) {
+            update.kept_packages.retain(|kept| *kept != id);
+        } else {
+            update.kept_packages.push(id);
+        }
+        if let Err(e) = update.save() {
+            status.set(format!("Error saving settings: {}", e));
+        }
+        settings.set(update);
+    };
+
+    rsx! {
+        div {
+            style: "background: white; border: 1px solid #ddd; border-radius: 5px; padding: 15px; margin-top: 20px;",
+            h2 {
+                style: "margin: 0 0 10px 0; font-size: 16px; color: #1b2838;",
+                "Update Plan"
+            }
+            p {
+                style: "margin: 0 0 8px 0; font-size: 13px; color: #666;",
+                "{new_count} new, {updated_count} updated, {unchanged_count} unchanged, {removed_count} removed"
+            }
+            for entry in plan.iter().filter(|entry| entry.is_change() && !matches!(entry.action, PlanAction::Orphaned { .. })) {
+                div {
+                    key: "{entry.id}",
+                    style: "font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    match &entry.action {
+                        PlanAction::Install => rsx! { span { style: "color: #5c7e10;", "+ {entry.name} v{entry.version}" } },
+                        PlanAction::Update { from } => rsx! { span { style: "color: #007bff;", "↑ {entry.name} v{from} → v{entry.version}" } },
+                        PlanAction::Remove => rsx! { span { style: "color: #800000;", "− {entry.name}" } },
+                        PlanAction::Unchanged | PlanAction::Orphaned { .. } => rsx! {},
+                    }
+                }
+            }
+            for entry in plan.iter().filter(|entry| matches!(entry.action, PlanAction::Orphaned { .. })) {
+                div {
+                    key: "{entry.id}",
+                    style: "display: flex; justify-content: space-between; align-items: center; font-size: 13px; color: #666; padding: 4px 0; border-top: 1px solid #eee;",
+                    if entry.is_change() {
+                        span { style: "color: #800000;", "− {entry.name} (no longer in modpack)" }
+                    } else {
+                        span { "{entry.name} v{entry.version} (kept, not in modpack)" }
+                    }
+                    button {
+                        style: "background: #666; color: white; padding: 4px 8px; border: none; border-radius: 3px; cursor: pointer; font-size: 12px;",
+                        onclick: {
+                            let id = entry.id.clone();
+                            move |_| toggle_keep(id.clone())
+                        },
+                        if entry.is_change() { "Keep" } else { "Remove" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn listed(id: &str, version: &str, enabled: bool) -> Mod {
+        let name = id.split_once('-').map_or(id, |(_, name)| name);
+        Mod {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            icon_url: String::new(),
+            download_url: String::new(),
+            version: version.to_string(),
+            enabled,
+            from: None,
+            to: Some(format!("BepInEx/plugins/{}", name)),
+            required_by_server: false,
+            sha256: None,
+            requirement: crate::bepmod::Requirement::Recommended,
+            dependencies: Vec::new(),
+            category: None,
+            group: None,
+            conflicts_with: Vec::new(),
+            replaces: Vec::new(),
+            thunderstore: true,
+        }
+    }
+
+    fn installed(id: &str, version: &str) -> InstallReceipt {
+        InstallReceipt {
+            id: id.to_string(),
+            name: id.split_once('-').map_or(id, |(_, name)| name).to_string(),
+            version: version.to_string(),
+            files: Vec::new(),
+            disabled: false,
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn action<'a>(plan: &'a [PlanEntry], id: &str) -> Option<&'a PlanAction> {
+        plan.iter().find(|entry| entry.id == id).map(|entry| &entry.action)
+    }
+
+    #[test]
+    fn packages_dropped_from_the_manifest_are_orphans_unless_kept() {
+        let mods = [listed("A-Listed", "1.0.0", true), listed("A-Deselected", "1.0.0", false)];
+        let receipts = [installed("A-Listed", "1.0.0"), installed("A-Deselected", "1.0.0"), installed("A-Dropped", "1.0.0"), installed("A-Kept", "1.0.0")];
+
+        let plan = build_plan(Path::new("missing"), &mods, Some(&ids(&["A-Listed", "A-Deselected"])), &receipts, &ids(&["A-Kept"]));
+        assert_eq!(action(&plan, "A-Listed"), Some(&PlanAction::Unchanged));
+        assert_eq!(action(&plan, "A-Deselected"), Some(&PlanAction::Remove));
+        assert_eq!(action(&plan, "A-Dropped"), Some(&PlanAction::Orphaned { keep: false }));
+        assert_eq!(action(&plan, "A-Kept"), Some(&PlanAction::Orphaned { keep: true }));
+    }
+
+    #[test]
+    fn bepinex_is_never_removed() {
+        let mods = [listed("BepInEx-BepInExPack", "5.4.2202", false), listed("A-Mod", "1.0.0", true)];
+        let receipts = [installed("BepInEx-BepInExPack", "5.4.2202"), installed("denikson-BepInExPack_Valheim", "5.4.2202")];
+
+        let plan = build_plan(Path::new("missing"), &mods, Some(&ids(&["BepInEx-BepInExPack", "A-Mod"])), &receipts, &[]);
+        assert!(plan.iter().all(|entry| !entry.is_removal()), "{:?}", plan);
+        assert_eq!(action(&plan, "A-Mod"), Some(&PlanAction::Install));
+    }
+
+    #[test]
+    fn an_empty_or_partial_manifest_orphans_nothing() {
+        let receipts = [installed("A-Installed", "1.0.0"), installed("A-Listed", "1.0.0")];
+
+        assert!(build_plan(Path::new("missing"), &[], Some(&[]), &receipts, &[]).is_empty());
+        assert!(build_plan(Path::new("missing"), &[], None, &receipts, &[]).is_empty());
+    }
+
+    #[test]
+    fn installs_without_a_receipt_are_found_by_their_manifest_name() {
+        let valheim_path: PathBuf = std::env::temp_dir().join(format!("update-test-{}", std::process::id()));
+        for (folder, name) in [("Mod", "Mod"), ("Other", "SomethingElse")] {
+            let dir = valheim_path.join("BepInEx/plugins").join(folder);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("manifest.json"), format!("\u{feff}{{\"name\": \"{}\", \"version_number\": \"1.0.0\"}}", name)).unwrap();
+        }
+        let mods = [listed("A-Mod", "1.1.0", true), listed("A-Other", "1.0.0", true)];
+
+        let plan = build_plan(&valheim_path, &mods, Some(&ids(&["A-Mod", "A-Other"])), &[], &[]);
+        std::fs::remove_dir_all(&valheim_path).unwrap();
+        assert_eq!(action(&plan, "A-Mod"), Some(&PlanAction::Update { from: "1.0.0".to_string() }));
+        assert_eq!(action(&plan, "A-Other"), Some(&PlanAction::Install));
+    }
+}